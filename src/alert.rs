@@ -0,0 +1,293 @@
+//! Threshold alert rules.
+use crate::config::Config;
+use crate::metrics::{self, Metrics};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A rule such as `statistics.run_queue > 50 for 10s` or `memory.total_bytes > 8GiB`.
+///
+/// The syntax is `METRIC OP THRESHOLD [for DURATION]`:
+/// - `METRIC` is a metric name, which may contain glob wildcards (`*` and `?`)
+/// - `OP` is one of `>`, `>=`, `<`, `<=`, `==` and `!=`
+/// - `THRESHOLD` is a number optionally followed by a unit (`%`, `/s`, `K`, `M`, `G`, `T`, `KiB`, `MiB`, `GiB` or `TiB`)
+/// - `DURATION` is a number followed by `ms`, `s`, `m` or `h`
+///
+/// Counters are compared by their per-second values.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct AlertRule {
+    source: String,
+    metric: String,
+    op: Op,
+    threshold: f64,
+    duration: Duration,
+}
+
+impl AlertRule {
+    fn is_satisfied(&self, value: f64) -> bool {
+        self.op.apply(value, self.threshold)
+    }
+}
+
+impl FromStr for AlertRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = s.split_whitespace().collect::<Vec<_>>();
+        let (metric, op, threshold, duration) = match tokens.as_slice() {
+            [metric, op, threshold] => (metric, op, threshold, None),
+            [metric, op, threshold, "for", duration] => (metric, op, threshold, Some(duration)),
+            _ => anyhow::bail!(
+                "expected an alert rule like `METRIC OP THRESHOLD [for DURATION]`, but got {s:?}"
+            ),
+        };
+        let op = Op::parse(op)
+            .ok_or_else(|| anyhow::anyhow!("unknown operator {op:?} in alert rule {s:?}"))?;
+        let threshold = parse_threshold(threshold).ok_or_else(|| {
+            anyhow::anyhow!("invalid threshold {threshold:?} in alert rule {s:?}")
+        })?;
+        let duration = duration
+            .map(|d| {
+                parse_duration(d)
+                    .ok_or_else(|| anyhow::anyhow!("invalid duration {d:?} in alert rule {s:?}"))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Self {
+            source: s.to_owned(),
+            metric: metric.to_string(),
+            op,
+            threshold,
+            duration,
+        })
+    }
+}
+
+impl TryFrom<String> for AlertRule {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Comparison operator used in alert rules and `check` assertions.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Op {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s {
+            ">" => Some(Self::Gt),
+            ">=" => Some(Self::Ge),
+            "<" => Some(Self::Lt),
+            "<=" => Some(Self::Le),
+            "==" => Some(Self::Eq),
+            "!=" => Some(Self::Ne),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn apply(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Gt => value > threshold,
+            Self::Ge => value >= threshold,
+            Self::Lt => value < threshold,
+            Self::Le => value <= threshold,
+            Self::Eq => value == threshold,
+            Self::Ne => value != threshold,
+        }
+    }
+}
+
+fn split_number(s: &str) -> Option<(f64, &str)> {
+    let i = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    Some((s[..i].parse().ok()?, &s[i..]))
+}
+
+/// Parses a threshold such as `50`, `90%`, `8GiB` or `5000/s`.
+pub(crate) fn parse_threshold(s: &str) -> Option<f64> {
+    let (n, unit) = split_number(s)?;
+    let unit = unit.strip_suffix("/s").unwrap_or(unit);
+    let scale = match unit {
+        "" | "%" => 1.0,
+        "K" | "KB" => 1e3,
+        "M" | "MB" => 1e6,
+        "G" | "GB" => 1e9,
+        "T" | "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(n * scale)
+}
+
+/// Parses a duration such as `500ms`, `10s`, `5m` or `1h`.
+pub(crate) fn parse_duration(s: &str) -> Option<Duration> {
+    let (n, unit) = split_number(s)?;
+    let secs = match unit {
+        "ms" => n / 1000.0,
+        "s" => n,
+        "m" => n * 60.0,
+        "h" => n * 60.0 * 60.0,
+        _ => return None,
+    };
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+/// An alert passed to the hook command (as JSON) and shown in the alerts pane.
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub state: AlertState,
+    pub rule: String,
+    pub node: String,
+    pub metric: String,
+    pub value: f64,
+    pub start_time: chrono::DateTime<chrono::Local>,
+}
+
+/// Evaluates alert rules against the metrics collected from a node.
+#[derive(Debug)]
+pub struct AlertEvaluator {
+    rules: Vec<AlertRule>,
+    hook: Option<String>,
+    node: String,
+    start_time: chrono::DateTime<chrono::Local>,
+
+    // The keys are pairs of a rule index and a metric name.
+    pending: BTreeMap<(usize, String), Duration>,
+    firing: BTreeMap<(usize, String), Alert>,
+}
+
+impl AlertEvaluator {
+    pub fn new(config: &Config, node: String, start_time: chrono::DateTime<chrono::Local>) -> Self {
+        Self {
+            rules: config.alerts.clone(),
+            hook: config.alert_hook.clone(),
+            node,
+            start_time,
+            pending: BTreeMap::new(),
+            firing: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the alerts firing after evaluating the given metrics.
+    pub fn evaluate(&mut self, metrics: &Metrics) -> Vec<Alert> {
+        if metrics.disconnected {
+            return self.firing.values().cloned().collect();
+        }
+
+        // Keys whose metric has a value in this sample. The state of the other keys is kept as is
+        // because their metrics are missing (e.g., the collector failed) rather than recovered.
+        let mut observed = BTreeSet::new();
+        let mut satisfied = BTreeSet::new();
+        for (i, rule) in self.rules.iter().enumerate() {
+            for (name, value) in &metrics.items {
+                if !metrics::glob_match(&rule.metric, name) {
+                    continue;
+                }
+                let Some(value) = value.as_f64() else {
+                    continue;
+                };
+                observed.insert((i, name.clone()));
+                if !rule.is_satisfied(value) {
+                    continue;
+                }
+
+                let key = (i, name.clone());
+                satisfied.insert(key.clone());
+                let since = *self.pending.entry(key.clone()).or_insert(metrics.timestamp);
+                if let Some(alert) = self.firing.get_mut(&key) {
+                    alert.value = value;
+                } else if metrics.timestamp - since >= rule.duration {
+                    let alert = Alert {
+                        state: AlertState::Firing,
+                        rule: rule.source.clone(),
+                        node: self.node.clone(),
+                        metric: name.clone(),
+                        value,
+                        start_time: self.start_time + since,
+                    };
+                    log::info!("alert fired: {} ({}={})", alert.rule, alert.metric, value);
+                    self.run_hook(&alert);
+                    self.firing.insert(key, alert);
+                }
+            }
+        }
+
+        self.pending
+            .retain(|key, _| satisfied.contains(key) || !observed.contains(key));
+        let resolved = self
+            .firing
+            .keys()
+            .filter(|key| observed.contains(*key) && !satisfied.contains(*key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in resolved {
+            let mut alert = self.firing.remove(&key).expect("unreachable");
+            alert.state = AlertState::Resolved;
+            if let Some(value) = metrics.items.get(&key.1).and_then(|v| v.as_f64()) {
+                alert.value = value;
+            }
+            log::info!("alert resolved: {} ({})", alert.rule, alert.metric);
+            self.run_hook(&alert);
+        }
+
+        self.firing.values().cloned().collect()
+    }
+
+    fn run_hook(&self, alert: &Alert) {
+        let Some(command) = &self.hook else {
+            return;
+        };
+        if let Err(e) = spawn_hook(command, alert) {
+            log::warn!("faild to run alert hook command {command:?}: {e}");
+        }
+    }
+}
+
+fn spawn_hook(command: &str, alert: &Alert) -> anyhow::Result<()> {
+    let json = serde_json::to_vec(alert)?;
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("unreachable");
+
+    // Not to block the polling thread.
+    std::thread::spawn(move || {
+        if let Err(e) = stdin.write_all(&json) {
+            log::warn!("faild to write an alert to the hook command: {e}");
+        }
+        std::mem::drop(stdin);
+        if let Err(e) = child.wait() {
+            log::warn!("faild to wait the hook command: {e}");
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Non-interactive assertions on metrics for CI and health probes.
+use crate::alert::{self, Op};
+use crate::metrics::{self, Header, MetricValue, Metrics, MetricsPoller};
+use crate::{record, CheckArgs, Command};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::time::Duration;
+
+const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// An assertion such as `max utilization.scheduler < 90%` or `avg statistics.garbage_collection < 5000/s`.
+///
+/// The syntax is `AGGREGATION METRIC OP THRESHOLD`:
+/// - `AGGREGATION` is one of `min`, `max`, `avg` and `last`
+/// - `METRIC`, `OP` and `THRESHOLD` are the same as in alert rules (see [`alert::AlertRule`])
+#[derive(Debug, Clone)]
+pub struct Assertion {
+    source: String,
+    aggregation: Aggregation,
+    metric: String,
+    op: Op,
+    threshold: f64,
+}
+
+impl FromStr for Assertion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = s.split_whitespace().collect::<Vec<_>>();
+        let [aggregation, metric, op, threshold] = tokens.as_slice() else {
+            anyhow::bail!(
+                "expected an assertion like `AGGREGATION METRIC OP THRESHOLD`, but got {s:?}"
+            );
+        };
+        let aggregation = match *aggregation {
+            "min" => Aggregation::Min,
+            "max" => Aggregation::Max,
+            "avg" => Aggregation::Avg,
+            "last" => Aggregation::Last,
+            _ => anyhow::bail!("unknown aggregation {aggregation:?} in assertion {s:?}"),
+        };
+        let op = Op::parse(op)
+            .ok_or_else(|| anyhow::anyhow!("unknown operator {op:?} in assertion {s:?}"))?;
+        let threshold = alert::parse_threshold(threshold)
+            .ok_or_else(|| anyhow::anyhow!("invalid threshold {threshold:?} in assertion {s:?}"))?;
+        Ok(Self {
+            source: s.to_owned(),
+            aggregation,
+            metric: metric.to_string(),
+            op,
+            threshold,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Aggregation {
+    Min,
+    Max,
+    Avg,
+    Last,
+}
+
+#[derive(Debug)]
+struct Aggregated {
+    min: Option<f64>,
+    max: Option<f64>,
+
+    // The sum and the number of the samples having a value
+    // (e.g., the first sample of a counter has no rate).
+    sum: f64,
+    valued: usize,
+
+    last: MetricValue,
+}
+
+impl Aggregated {
+    fn new(value: &MetricValue) -> Self {
+        let v = value.as_f64();
+        Self {
+            min: v,
+            max: v,
+            sum: v.unwrap_or_default(),
+            valued: usize::from(v.is_some()),
+            last: value.clone(),
+        }
+    }
+
+    fn add(&mut self, value: &MetricValue) {
+        if let Some(v) = value.as_f64() {
+            self.min = Some(self.min.map_or(v, |min| min.min(v)));
+            self.max = Some(self.max.map_or(v, |max| max.max(v)));
+            self.sum += v;
+            self.valued += 1;
+        }
+        self.last = value.clone();
+    }
+
+    fn get(&self, aggregation: Aggregation) -> Option<f64> {
+        match aggregation {
+            Aggregation::Min => self.min,
+            Aggregation::Max => self.max,
+            Aggregation::Avg => (self.valued > 0).then(|| self.sum / self.valued as f64),
+            Aggregation::Last => self.last.as_f64(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct NodeSamples {
+    count: usize,
+    items: BTreeMap<String, Aggregated>,
+}
+
+impl NodeSamples {
+    fn add(&mut self, metrics: &Metrics) {
+        self.count += 1;
+        for (name, value) in &metrics.items {
+            if let Some(x) = self.items.get_mut(name) {
+                x.add(value);
+            } else {
+                self.items.insert(name.clone(), Aggregated::new(value));
+            }
+        }
+    }
+}
+
+/// Returns `Ok(false)` if any of the assertions failed.
+pub fn run(args: CheckArgs) -> anyhow::Result<bool> {
+    let (header, samples) = if let Some(path) = &args.replay {
+        collect_from_record_file(path)?
+    } else {
+        collect_from_nodes(&args)?
+    };
+
+    let mut passed = true;
+    for (i, node) in header.nodes().iter().enumerate() {
+        let samples = &samples[i];
+        println!("{} ({} samples)", node.node_name, samples.count);
+        for assertion in &args.assertions {
+            let mut matched = false;
+            for (name, aggregated) in &samples.items {
+                if !metrics::glob_match(&assertion.metric, name) {
+                    continue;
+                }
+                matched = true;
+                let (ok, actual) = match aggregated.get(assertion.aggregation) {
+                    Some(v) => (assertion.op.apply(v, assertion.threshold), v.to_string()),
+                    None => (false, "n/a".to_owned()),
+                };
+                passed &= ok;
+                println!(
+                    "  [{}] {} ({}: {actual})",
+                    if ok { "PASS" } else { "FAIL" },
+                    assertion.source,
+                    name
+                );
+            }
+            if !matched {
+                passed = false;
+                println!("  [FAIL] {} (no such metric)", assertion.source);
+            }
+        }
+    }
+    Ok(passed)
+}
+
+fn collect_from_record_file(path: &std::path::Path) -> anyhow::Result<(Header, Vec<NodeSamples>)> {
+    let (header, metrics_log) = record::load_record_file(path)?;
+    let mut samples = header
+        .nodes()
+        .iter()
+        .map(|_| NodeSamples::default())
+        .collect::<Vec<_>>();
+    for metrics in metrics_log.iter().filter(|m| !m.disconnected) {
+        if let Some(i) = header.node_index(metrics) {
+            samples[i].add(metrics);
+        }
+    }
+    Ok((header, samples))
+}
+
+fn collect_from_nodes(args: &CheckArgs) -> anyhow::Result<(Header, Vec<NodeSamples>)> {
+    let poller = MetricsPoller::start_thread(Command::Run(args.to_run_args()))?;
+    let header = poller.header().clone();
+    let mut samples = header
+        .nodes()
+        .iter()
+        .map(|_| NodeSamples::default())
+        .collect::<Vec<_>>();
+
+    // The first sample of each node is only used as the baseline of the counter rates
+    // (otherwise, `-n 1` couldn't check any counters).
+    let mut warmed_up = vec![false; samples.len()];
+    while samples.iter().any(|s| s.count < args.samples.get()) {
+        let metrics = match poller.poll_metrics(POLL_TIMEOUT) {
+            Ok(metrics) => metrics,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                anyhow::bail!("metrics polling threads have terminated unexpectedly")
+            }
+        };
+        let Some(i) = header.node_index(&metrics) else {
+            continue;
+        };
+        let node_name = &header.nodes()[i].node_name;
+        anyhow::ensure!(!metrics.disconnected, "lost connection to {node_name}");
+        if !std::mem::replace(&mut warmed_up[i], true) {
+            continue;
+        }
+        if samples[i].count < args.samples.get() {
+            samples[i].add(&metrics);
+        }
+    }
+    Ok((header, samples))
+}
@@ -0,0 +1,968 @@
+//! Collectors of the groups of metrics polled from the target nodes.
+//!
+//! Applications embedding `erldash` can collect their own metrics by implementing [`Collector`]
+//! and passing it to [`MetricsPoller::start_thread_with_collectors()`](crate::metrics::MetricsPoller::start_thread_with_collectors).
+use crate::config::{Config, CustomMetric, CustomMetricKind};
+use crate::erlang::{
+    self, AllocatorSizes, Call, DistConnection, MSAccThread, RpcClient, SchedulerWallTime,
+};
+use crate::metrics::{MetricValue, Metrics};
+use crate::RunArgs;
+use erl_dist::term::Term;
+use futures::future::LocalBoxFuture;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// A collector of a group of metrics (e.g., `memory`).
+///
+/// A collector is created for each target node and called once per poll.
+/// If it fails, the metrics of the group are shown as `n/a` for that sample
+/// while the other collectors are not affected.
+pub trait Collector: Send {
+    /// Name of the group (shown in the "Collector Errors" pane).
+    fn name(&self) -> &str;
+
+    /// Called each time `erldash` (re)connects to the target node.
+    ///
+    /// This is the place to fetch static information about the node (e.g., the word size).
+    fn init<'a>(
+        &'a mut self,
+        _rpc_client: &'a RpcClient,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Returns the glob patterns of the metrics inserted by this collector.
+    ///
+    /// The collector is not run if none of the patterns are selected by `--metrics` and `--exclude-metrics` options.
+    /// If empty (the default), the collector is skipped only if its name is unselected.
+    fn metrics(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the calls evaluated in a batch at the beginning of each poll (see [`RpcClient::prefetch()`]).
+    ///
+    /// The results are consumed by the identical calls made in [`Collector::collect()`].
+    fn batch_calls(&self) -> Vec<Call> {
+        Vec::new()
+    }
+
+    /// Inserts the metrics of the group into `metrics`.
+    ///
+    /// `prev` is the previous sample of the node (empty right after (re)connecting).
+    /// The rates of counters are derived from `prev` after all the collectors have finished.
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>>;
+}
+
+impl std::fmt::Debug for dyn Collector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Collector")
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+/// Creates a collector for each target node.
+pub type CollectorFactory = Arc<dyn Fn() -> Box<dyn Collector> + Send + Sync>;
+
+/// Returns the built-in collectors followed by the collectors of the custom metrics in `config`.
+///
+/// The microstate accounting collector comes first as it resets the counters right after reading them.
+pub(crate) fn builtin_collectors(args: &RunArgs, config: &Config) -> Vec<Box<dyn Collector>> {
+    let mut collectors: Vec<Box<dyn Collector>> = Vec::new();
+    if !args.connection.read_only {
+        collectors.push(Box::new(MsaccCollector));
+    }
+    collectors.push(Box::new(SchedulerWallTimeCollector {
+        read_only: args.connection.read_only,
+        ..Default::default()
+    }));
+    collectors.push(Box::new(SystemInfoCollector));
+    collectors.push(Box::<StatisticsCollector>::default());
+    collectors.push(Box::new(MemoryCollector));
+    collectors.push(Box::<AllocatorCollector>::default());
+    collectors.push(Box::<EtsCollector>::default());
+    collectors.push(Box::<DistributionCollector>::default());
+    for metric in &config.custom_metrics {
+        collectors.push(Box::new(CustomMetricCollector::new(metric.clone())));
+    }
+    collectors
+}
+
+#[derive(Debug)]
+struct MsaccCollector;
+
+impl Collector for MsaccCollector {
+    fn name(&self) -> &str {
+        "msacc"
+    }
+
+    // `statistics.garbage_collection.time_share` is also derived from microstate accounting,
+    // but it's not listed here so that excluding `utilization.*` is enough to disable microstate accounting.
+    fn metrics(&self) -> Vec<String> {
+        to_strings(&["utilization.*"])
+    }
+
+    fn batch_calls(&self) -> Vec<Call> {
+        // `reset` needs to be evaluated right after reading the counters.
+        vec![
+            Call::statistics("microstate_accounting"),
+            Call::system_flag("microstate_accounting", "reset"),
+        ]
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let msacc = rpc_client.get_statistics_microstate_accounting().await?;
+            rpc_client
+                .set_system_flag_bool("microstate_accounting", "reset")
+                .await?;
+            insert_msacc_metrics(metrics, &msacc);
+            insert_gc_time_metrics(metrics, &msacc);
+            Ok(())
+        })
+    }
+}
+
+fn insert_msacc_metrics(metrics: &mut Metrics, msacc_threads: &[MSAccThread]) {
+    let mut aggregated_per_type = BTreeMap::<_, ThreadTime>::new();
+    let mut aggregated_per_state_per_type = BTreeMap::<_, BTreeMap<&str, u64>>::new();
+    let mut aggregated_per_thread_per_type = BTreeMap::<_, BTreeMap<u64, ThreadTime>>::new();
+
+    for thread in msacc_threads {
+        let x = aggregated_per_type.entry(&thread.thread_type).or_default();
+        let realtime = thread.counters.values().copied().sum::<u64>();
+        let sleeptime = thread.counters["sleep"];
+        x.realtime += realtime;
+        x.runtime += realtime - sleeptime;
+
+        let x = aggregated_per_thread_per_type
+            .entry(&thread.thread_type)
+            .or_default()
+            .entry(thread.thread_id)
+            .or_default();
+        x.realtime += realtime;
+        x.runtime += realtime - sleeptime;
+
+        for (state, value) in &thread.counters {
+            *aggregated_per_state_per_type
+                .entry(&thread.thread_type)
+                .or_default()
+                .entry(state)
+                .or_default() += *value;
+        }
+    }
+    for (ty, time) in aggregated_per_type {
+        let root_name = format!("utilization.{ty}");
+        metrics.insert(&root_name, MetricValue::utilization(time.utilization()));
+        for (state, value) in &aggregated_per_state_per_type[ty] {
+            let u = *value as f64 / time.realtime as f64 * 100.0;
+            metrics.insert(
+                &format!("{root_name}.state.{state}"),
+                MetricValue::utilization_with_parent(u, &root_name),
+            );
+        }
+
+        insert_thread_utilization_metrics(metrics, &root_name, &aggregated_per_thread_per_type[ty]);
+    }
+}
+
+/// Inserts the share of the time the schedulers spent on garbage collection.
+///
+/// The `gc_full` state is only available if the runtime is built with extra microstate accounting states.
+fn insert_gc_time_metrics(metrics: &mut Metrics, msacc_threads: &[MSAccThread]) {
+    let mut time = ThreadTime::default();
+    for thread in msacc_threads
+        .iter()
+        .filter(|t| matches!(t.thread_type.as_str(), "scheduler" | "dirty_cpu_scheduler"))
+    {
+        time.realtime += thread.counters.values().copied().sum::<u64>();
+        time.runtime += ["gc", "gc_full"]
+            .iter()
+            .filter_map(|state| thread.counters.get(*state))
+            .sum::<u64>();
+    }
+    if time.realtime == 0 {
+        return;
+    }
+    metrics.insert(
+        "statistics.garbage_collection.time_share",
+        MetricValue::utilization_with_parent(time.utilization(), "statistics.garbage_collection"),
+    );
+}
+
+/// Derives scheduler utilization from the difference between `scheduler_wall_time` samples.
+///
+/// The `scheduler_wall_time` root is the utilization of the normal schedulers and
+/// has a child for each normal, dirty CPU and dirty IO scheduler.
+/// In read-only mode, `utilization.*` metrics are also derived from the samples
+/// instead of microstate accounting.
+#[derive(Debug, Default)]
+struct SchedulerWallTimeCollector {
+    read_only: bool,
+    schedulers: u64,
+    dirty_cpu_schedulers: u64,
+    prev: Vec<SchedulerWallTime>,
+
+    // Whether the `scheduler_wall_time` flag was enabled when the connection was established.
+    enabled: bool,
+}
+
+impl SchedulerWallTimeCollector {
+    fn insert_metrics(
+        &mut self,
+        metrics: &mut Metrics,
+        scheduler_wall_time: Vec<SchedulerWallTime>,
+    ) {
+        let prev = std::mem::replace(&mut self.prev, scheduler_wall_time);
+        let mut aggregated_per_thread_per_type = BTreeMap::<_, BTreeMap<u64, ThreadTime>>::new();
+        for curr in &self.prev {
+            let Some(prev) = prev.iter().find(|x| x.scheduler_id == curr.scheduler_id) else {
+                continue;
+            };
+            let realtime = curr.total_time.saturating_sub(prev.total_time);
+            if realtime == 0 {
+                continue;
+            }
+            let runtime = curr.active_time.saturating_sub(prev.active_time);
+
+            // Scheduler IDs are assigned to normal, dirty CPU and dirty IO schedulers in this order.
+            let id = curr.scheduler_id;
+            let (ty, thread_id) = if id <= self.schedulers {
+                ("scheduler", id)
+            } else if id <= self.schedulers + self.dirty_cpu_schedulers {
+                ("dirty_cpu_scheduler", id - self.schedulers)
+            } else {
+                (
+                    "dirty_io_scheduler",
+                    id - self.schedulers - self.dirty_cpu_schedulers,
+                )
+            };
+            aggregated_per_thread_per_type
+                .entry(ty)
+                .or_default()
+                .insert(thread_id, ThreadTime { runtime, realtime });
+        }
+
+        let root_name = "scheduler_wall_time";
+        if let Some(threads) = aggregated_per_thread_per_type.get("scheduler") {
+            let total = ThreadTime::sum(threads.values());
+            metrics.insert(root_name, MetricValue::utilization(total.utilization()));
+        }
+        for (ty, threads) in &aggregated_per_thread_per_type {
+            let id_width = threads.keys().map(|id| id / 10 + 1).max().unwrap_or(1) as usize;
+            for (thread_id, time) in threads {
+                metrics.insert(
+                    &format!("{root_name}.{ty}.{:0id_width$}", thread_id),
+                    MetricValue::utilization_with_parent(time.utilization(), root_name),
+                );
+            }
+        }
+
+        if !self.read_only {
+            return;
+        }
+        for (ty, threads) in aggregated_per_thread_per_type {
+            let root_name = format!("utilization.{ty}");
+            let total = ThreadTime::sum(threads.values());
+            metrics.insert(&root_name, MetricValue::utilization(total.utilization()));
+            insert_thread_utilization_metrics(metrics, &root_name, &threads);
+        }
+    }
+}
+
+impl Collector for SchedulerWallTimeCollector {
+    fn name(&self) -> &str {
+        "scheduler_wall_time"
+    }
+
+    fn metrics(&self) -> Vec<String> {
+        let mut patterns = to_strings(&["scheduler_wall_time", "scheduler_wall_time.*"]);
+        if self.read_only {
+            patterns.push("utilization.*".to_owned());
+        }
+        patterns
+    }
+
+    fn init<'a>(&'a mut self, rpc_client: &'a RpcClient) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.schedulers = rpc_client.get_system_info_u64("schedulers").await?;
+            self.dirty_cpu_schedulers = rpc_client
+                .get_system_info_u64("dirty_cpu_schedulers")
+                .await?;
+            self.prev.clear();
+            self.enabled = rpc_client
+                .get_statistics_scheduler_wall_time()
+                .await?
+                .is_some();
+            Ok(())
+        })
+    }
+
+    fn batch_calls(&self) -> Vec<Call> {
+        vec![Call::statistics("scheduler_wall_time_all")]
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            match rpc_client.get_statistics_scheduler_wall_time().await? {
+                Some(scheduler_wall_time) => {
+                    let is_second_poll = !self.prev.is_empty();
+                    self.insert_metrics(metrics, scheduler_wall_time);
+                    anyhow::ensure!(
+                        !is_second_poll || metrics.items.contains_key("scheduler_wall_time"),
+                        "the `scheduler_wall_time` system flag has been reset on the target node since the previous poll"
+                    );
+                }
+                None => {
+                    // The metrics are marked as unavailable in the header if the flag was disabled from the beginning.
+                    anyhow::ensure!(
+                        !self.enabled,
+                        "the `scheduler_wall_time` system flag has been disabled on the target node"
+                    );
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug)]
+struct SystemInfoCollector;
+
+const SYSTEM_INFO_ITEMS: &[&str] = &["process_count", "port_count", "atom_count", "ets_count"];
+
+impl Collector for SystemInfoCollector {
+    fn name(&self) -> &str {
+        "system_info"
+    }
+
+    fn metrics(&self) -> Vec<String> {
+        to_strings(&["system_info.*"])
+    }
+
+    fn batch_calls(&self) -> Vec<Call> {
+        SYSTEM_INFO_ITEMS
+            .iter()
+            .map(|item| Call::system_info(item))
+            .collect()
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            for item in SYSTEM_INFO_ITEMS {
+                let value = rpc_client.get_system_info_u64(item).await?;
+                metrics.insert(&format!("system_info.{item}"), MetricValue::gauge(value));
+            }
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct StatisticsCollector {
+    wordsize: u64,
+}
+
+impl Collector for StatisticsCollector {
+    fn name(&self) -> &str {
+        "statistics"
+    }
+
+    fn metrics(&self) -> Vec<String> {
+        to_strings(&["statistics.*"])
+    }
+
+    fn init<'a>(&'a mut self, rpc_client: &'a RpcClient) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.wordsize = rpc_client.get_system_info_u64("wordsize").await?;
+            Ok(())
+        })
+    }
+
+    fn batch_calls(&self) -> Vec<Call> {
+        [
+            "context_switches",
+            "exact_reductions",
+            "garbage_collection",
+            "runtime",
+            "io",
+            "run_queue_lengths_all",
+        ]
+        .into_iter()
+        .map(Call::statistics)
+        .collect()
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let context_switches = rpc_client
+                .get_statistics_1st_u64("context_switches")
+                .await?;
+            metrics.insert(
+                "statistics.context_switches",
+                MetricValue::counter(context_switches),
+            );
+
+            let exact_reductions = rpc_client
+                .get_statistics_1st_u64("exact_reductions")
+                .await?;
+            metrics.insert(
+                "statistics.exact_reductions",
+                MetricValue::counter(exact_reductions),
+            );
+
+            let (gc_count, gc_words_reclaimed) =
+                rpc_client.get_statistics_garbage_collection().await?;
+            metrics.insert(
+                "statistics.garbage_collection",
+                MetricValue::counter(gc_count),
+            );
+            metrics.insert(
+                "statistics.garbage_collection.reclaimed_bytes",
+                MetricValue::counter_with_parent(
+                    gc_words_reclaimed * self.wordsize,
+                    "statistics.garbage_collection",
+                ),
+            );
+
+            let runtime = rpc_client.get_statistics_1st_u64("runtime").await?;
+            metrics.insert("statistics.runtime", MetricValue::counter(runtime));
+
+            let (in_bytes, out_bytes) = rpc_client.get_statistics_io().await?;
+            metrics.insert(
+                "statistics.io.total_bytes",
+                MetricValue::counter(in_bytes + out_bytes),
+            );
+            metrics.insert(
+                "statistics.io.input_bytes",
+                MetricValue::counter_with_parent(in_bytes, "statistics.io.total_bytes"),
+            );
+            metrics.insert(
+                "statistics.io.output_bytes",
+                MetricValue::counter_with_parent(out_bytes, "statistics.io.total_bytes"),
+            );
+
+            let run_queue_lengths = rpc_client
+                .get_statistics_u64_list("run_queue_lengths_all")
+                .await?;
+            let run_queue_total = run_queue_lengths.iter().copied().sum();
+            metrics.insert("statistics.run_queue", MetricValue::gauge(run_queue_total));
+
+            let width = run_queue_lengths.len() / 10 + 1;
+            for (i, n) in run_queue_lengths.into_iter().enumerate() {
+                metrics.insert(
+                    &format!("statistics.run_queue.{:0width$}", i),
+                    MetricValue::gauge_with_parent(n, "statistics.run_queue"),
+                );
+            }
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug)]
+struct MemoryCollector;
+
+impl Collector for MemoryCollector {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn metrics(&self) -> Vec<String> {
+        to_strings(&["memory.*"])
+    }
+
+    fn batch_calls(&self) -> Vec<Call> {
+        vec![Call::new("erlang", "memory")]
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut memory = rpc_client.get_memory().await?;
+            metrics.insert(
+                "memory.total_bytes",
+                MetricValue::gauge(memory.remove("total").expect("unreachable")),
+            );
+            for (k, v) in memory {
+                metrics.insert(
+                    &format!("memory.{k}_bytes"),
+                    MetricValue::gauge_with_parent(v, "memory.total_bytes"),
+                );
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Collects the used (block) and reserved (carrier) sizes of the allocators and their fragmentation.
+#[derive(Debug, Default)]
+struct AllocatorCollector {
+    // Only used to prefetch the sizes of the allocators.
+    allocators: Vec<String>,
+}
+
+impl Collector for AllocatorCollector {
+    fn name(&self) -> &str {
+        "allocator"
+    }
+
+    fn metrics(&self) -> Vec<String> {
+        to_strings(&["allocator.*"])
+    }
+
+    fn init<'a>(&'a mut self, rpc_client: &'a RpcClient) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.allocators = rpc_client.get_alloc_util_allocators().await?;
+            Ok(())
+        })
+    }
+
+    fn batch_calls(&self) -> Vec<Call> {
+        let mut calls = vec![Call::system_info("alloc_util_allocators")];
+        calls.extend(self.allocators.iter().map(|x| Call::allocator_sizes(x)));
+        calls
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let allocators = rpc_client.get_allocator_sizes().await?;
+            insert_allocator_metrics(metrics, &allocators);
+            Ok(())
+        })
+    }
+}
+
+fn insert_allocator_metrics(metrics: &mut Metrics, allocators: &[AllocatorSizes]) {
+    let mut aggregated_per_allocator = BTreeMap::<_, (u64, u64)>::new();
+    for x in allocators {
+        let sizes = aggregated_per_allocator.entry(&x.allocator).or_default();
+        sizes.0 += x.blocks_bytes;
+        sizes.1 += x.carriers_bytes;
+    }
+
+    let total_blocks = aggregated_per_allocator.values().map(|x| x.0).sum();
+    let total_carriers = aggregated_per_allocator.values().map(|x| x.1).sum();
+    metrics.insert("allocator.blocks_bytes", MetricValue::gauge(total_blocks));
+    metrics.insert(
+        "allocator.carriers_bytes",
+        MetricValue::gauge(total_carriers),
+    );
+    metrics.insert(
+        "allocator.fragmentation",
+        MetricValue::utilization(fragmentation(total_blocks, total_carriers)),
+    );
+    for (allocator, (blocks, carriers)) in aggregated_per_allocator {
+        metrics.insert(
+            &format!("allocator.blocks_bytes.{allocator}"),
+            MetricValue::gauge_with_parent(blocks, "allocator.blocks_bytes"),
+        );
+        metrics.insert(
+            &format!("allocator.carriers_bytes.{allocator}"),
+            MetricValue::gauge_with_parent(carriers, "allocator.carriers_bytes"),
+        );
+        metrics.insert(
+            &format!("allocator.fragmentation.{allocator}"),
+            MetricValue::utilization_with_parent(
+                fragmentation(blocks, carriers),
+                "allocator.fragmentation",
+            ),
+        );
+    }
+
+    let width = allocators
+        .iter()
+        .map(|x| x.instance / 10 + 1)
+        .max()
+        .unwrap_or(1) as usize;
+    for x in allocators {
+        metrics.insert(
+            &format!(
+                "allocator.fragmentation.{}.{:0width$}",
+                x.allocator, x.instance
+            ),
+            MetricValue::utilization_with_parent(
+                fragmentation(x.blocks_bytes, x.carriers_bytes),
+                &format!("allocator.fragmentation.{}", x.allocator),
+            ),
+        );
+    }
+}
+
+/// Returns the percentage of `carriers_bytes` not used by blocks.
+fn fragmentation(blocks_bytes: u64, carriers_bytes: u64) -> f64 {
+    if carriers_bytes == 0 {
+        0.0
+    } else {
+        100.0 - blocks_bytes as f64 / carriers_bytes as f64 * 100.0
+    }
+}
+
+/// The number of the ETS tables (with the largest memory) whose metrics are collected.
+const ETS_TOP_TABLES: usize = 20;
+
+#[derive(Debug, Default)]
+struct EtsCollector {
+    wordsize: u64,
+}
+
+impl Collector for EtsCollector {
+    fn name(&self) -> &str {
+        "ets"
+    }
+
+    fn metrics(&self) -> Vec<String> {
+        to_strings(&["ets.*"])
+    }
+
+    fn init<'a>(&'a mut self, rpc_client: &'a RpcClient) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.wordsize = rpc_client.get_system_info_u64("wordsize").await?;
+            Ok(())
+        })
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut tables = rpc_client.get_ets_table_sizes().await?;
+            metrics.insert(
+                "ets.size",
+                MetricValue::gauge(tables.iter().map(|t| t.size).sum()),
+            );
+            metrics.insert(
+                "ets.memory_bytes",
+                MetricValue::gauge(tables.iter().map(|t| t.memory).sum::<u64>() * self.wordsize),
+            );
+
+            // Only the largest tables are included to keep the samples (and record files) small.
+            tables.sort_by_key(|t| std::cmp::Reverse(t.memory));
+            for table in tables.iter().take(ETS_TOP_TABLES) {
+                metrics.insert(
+                    &format!("ets.size.{}", table.id),
+                    MetricValue::gauge_with_parent(table.size, "ets.size"),
+                );
+                metrics.insert(
+                    &format!("ets.memory_bytes.{}", table.id),
+                    MetricValue::gauge_with_parent(
+                        table.memory * self.wordsize,
+                        "ets.memory_bytes",
+                    ),
+                );
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Derives the distribution counters from consecutive samples of the connections.
+#[derive(Debug, Default)]
+struct DistributionCollector {
+    dist_buf_busy_limit: u64,
+
+    // Pairs of a remote node and the controller of the connection (`None` before the first poll).
+    prev_connections: Option<BTreeMap<String, String>>,
+    nodeups: u64,
+    nodedowns: u64,
+
+    // The number of polls in which the output queue of the connection exceeded `dist_buf_busy_limit`.
+    // This approximates how often the connection was busy (`busy_dist_port`), which is only
+    // observable via `erlang:system_monitor/2` and isn't counted here.
+    busy_samples: BTreeMap<String, u64>,
+}
+
+impl DistributionCollector {
+    fn insert_metrics(&mut self, metrics: &mut Metrics, connections: &[DistConnection]) {
+        let curr = connections
+            .iter()
+            .map(|c| (c.node.clone(), c.controller.clone()))
+            .collect::<BTreeMap<_, _>>();
+        if let Some(prev) = &self.prev_connections {
+            // A connection whose controller has changed was re-established between the polls.
+            for (node, controller) in &curr {
+                match prev.get(node) {
+                    None => self.nodeups += 1,
+                    Some(x) if x != controller => {
+                        self.nodedowns += 1;
+                        self.nodeups += 1;
+                    }
+                    Some(_) => {}
+                }
+            }
+            self.nodedowns += prev.keys().filter(|node| !curr.contains_key(*node)).count() as u64;
+        }
+        self.prev_connections = Some(curr);
+
+        metrics.insert(
+            "distribution.connections",
+            MetricValue::gauge(connections.len() as u64),
+        );
+        metrics.insert(
+            "distribution.nodeups",
+            MetricValue::counter_with_parent(self.nodeups, "distribution.connections"),
+        );
+        metrics.insert(
+            "distribution.nodedowns",
+            MetricValue::counter_with_parent(self.nodedowns, "distribution.connections"),
+        );
+
+        let packet_counts = connections
+            .iter()
+            .filter_map(|c| c.packet_counts.map(|counts| (&c.node, counts)))
+            .collect::<Vec<_>>();
+        for (root, i) in [
+            ("distribution.input_packets", 0),
+            ("distribution.output_packets", 1),
+        ] {
+            let count = |counts: (u64, u64)| if i == 0 { counts.0 } else { counts.1 };
+            metrics.insert(
+                root,
+                MetricValue::counter(packet_counts.iter().map(|(_, c)| count(*c)).sum()),
+            );
+            for (node, counts) in &packet_counts {
+                metrics.insert(
+                    &format!("{root}.{node}"),
+                    MetricValue::counter_with_parent(count(*counts), root),
+                );
+            }
+        }
+
+        // The following metrics are only available for the connections controlled by ports.
+        let ports = connections
+            .iter()
+            .filter_map(|c| c.port.as_ref().map(|port| (&c.node, port)))
+            .collect::<Vec<_>>();
+        for (node, port) in &ports {
+            if port.queue_size >= self.dist_buf_busy_limit {
+                *self.busy_samples.entry((*node).clone()).or_default() += 1;
+            }
+        }
+
+        let roots = [
+            "distribution.input_bytes",
+            "distribution.output_bytes",
+            "distribution.queue_size_bytes",
+            "distribution.busy_samples",
+        ];
+        metrics.insert(
+            roots[0],
+            MetricValue::counter(ports.iter().map(|(_, p)| p.input_bytes).sum()),
+        );
+        metrics.insert(
+            roots[1],
+            MetricValue::counter(ports.iter().map(|(_, p)| p.output_bytes).sum()),
+        );
+        metrics.insert(
+            roots[2],
+            MetricValue::gauge(ports.iter().map(|(_, p)| p.queue_size).sum()),
+        );
+        metrics.insert(
+            roots[3],
+            MetricValue::counter(self.busy_samples.values().copied().sum()),
+        );
+        for (node, port) in &ports {
+            metrics.insert(
+                &format!("{}.{node}", roots[0]),
+                MetricValue::counter_with_parent(port.input_bytes, roots[0]),
+            );
+            metrics.insert(
+                &format!("{}.{node}", roots[1]),
+                MetricValue::counter_with_parent(port.output_bytes, roots[1]),
+            );
+            metrics.insert(
+                &format!("{}.{node}", roots[2]),
+                MetricValue::gauge_with_parent(port.queue_size, roots[2]),
+            );
+            let busy_samples = self.busy_samples.get(*node).copied().unwrap_or(0);
+            metrics.insert(
+                &format!("{}.{node}", roots[3]),
+                MetricValue::counter_with_parent(busy_samples, roots[3]),
+            );
+        }
+    }
+}
+
+impl Collector for DistributionCollector {
+    fn name(&self) -> &str {
+        "distribution"
+    }
+
+    fn metrics(&self) -> Vec<String> {
+        to_strings(&["distribution.*"])
+    }
+
+    fn init<'a>(&'a mut self, rpc_client: &'a RpcClient) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            *self = Self {
+                dist_buf_busy_limit: rpc_client
+                    .get_system_info_u64("dist_buf_busy_limit")
+                    .await?,
+                ..Default::default()
+            };
+            Ok(())
+        })
+    }
+
+    fn batch_calls(&self) -> Vec<Call> {
+        vec![Call::system_info("dist_ctrl")]
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let connections = rpc_client.get_dist_connections().await?;
+            self.insert_metrics(metrics, &connections);
+            Ok(())
+        })
+    }
+}
+
+/// Collects a metric declared in the config file.
+///
+/// Each custom metric has its own collector, so a failure only affects the metric.
+#[derive(Debug)]
+struct CustomMetricCollector {
+    name: String,
+    metric: CustomMetric,
+    call: Call,
+}
+
+impl CustomMetricCollector {
+    fn new(metric: CustomMetric) -> Self {
+        Self {
+            name: format!("custom:{}", metric.name),
+            call: Call::from_config(&metric.module, &metric.function, &metric.args),
+            metric,
+        }
+    }
+
+    fn value(&self, term: Term) -> anyhow::Result<MetricValue> {
+        let term = erlang::term_at_path(term, &self.metric.path)?;
+        let parent = self.metric.parent.clone();
+        let value = match self.metric.kind {
+            CustomMetricKind::Gauge => MetricValue::Gauge {
+                value: erlang::term_to_u64(term)?,
+                parent,
+            },
+            CustomMetricKind::Counter => MetricValue::Counter {
+                raw_value: erlang::term_to_u64(term)?,
+                value: None,
+                parent,
+            },
+            CustomMetricKind::Utilization => MetricValue::Utilization {
+                value: erlang::term_to_f64(term)?,
+                parent,
+            },
+        };
+        Ok(value)
+    }
+}
+
+impl Collector for CustomMetricCollector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn metrics(&self) -> Vec<String> {
+        vec![self.metric.name.clone()]
+    }
+
+    fn batch_calls(&self) -> Vec<Call> {
+        vec![self.call.clone()]
+    }
+
+    fn collect<'a>(
+        &'a mut self,
+        rpc_client: &'a RpcClient,
+        _prev: &'a Metrics,
+        metrics: &'a mut Metrics,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let term = rpc_client.call_mfa(&self.call).await?;
+            metrics.insert(&self.metric.name, self.value(term)?);
+            Ok(())
+        })
+    }
+}
+
+fn to_strings(patterns: &[&str]) -> Vec<String> {
+    patterns.iter().map(|x| x.to_string()).collect()
+}
+
+fn insert_thread_utilization_metrics(
+    metrics: &mut Metrics,
+    root_name: &str,
+    threads: &BTreeMap<u64, ThreadTime>,
+) {
+    let id_width = threads.keys().map(|id| id / 10 + 1).max().unwrap_or(1) as usize;
+    for (thread_id, time) in threads {
+        metrics.insert(
+            &format!("{root_name}.thread.{:0id_width$}", thread_id),
+            MetricValue::utilization_with_parent(time.utilization(), root_name),
+        );
+    }
+}
+
+#[derive(Debug, Default)]
+struct ThreadTime {
+    runtime: u64,
+    realtime: u64,
+}
+
+impl ThreadTime {
+    fn sum<'a>(times: impl Iterator<Item = &'a ThreadTime>) -> Self {
+        let mut total = Self::default();
+        for time in times {
+            total.runtime += time.runtime;
+            total.realtime += time.realtime;
+        }
+        total
+    }
+
+    fn utilization(&self) -> f64 {
+        self.runtime as f64 / self.realtime as f64 * 100.0
+    }
+}
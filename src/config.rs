@@ -0,0 +1,83 @@
+//! Configuration file specified by `--config` option.
+use crate::alert::AlertRule;
+use crate::erlang::{CallArg, PathElement};
+use anyhow::Context;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Configuration written in JSON.
+///
+/// Example:
+///
+/// ```json
+/// {
+///   "alerts": ["statistics.run_queue > 50 for 10s", "memory.total_bytes > 8GiB"],
+///   "alert_hook": "/usr/local/bin/page-oncall",
+///   "custom_metrics": [
+///     {
+///       "name": "my_app.requests",
+///       "module": "my_app_metrics",
+///       "function": "get",
+///       "type": "counter",
+///       "path": ["requests"]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Alert rules evaluated against each metrics sample (see [`AlertRule`] for the syntax).
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+
+    /// Command executed via `sh -c` when an alert fires or resolves.
+    ///
+    /// The alert is passed to the command as JSON via stdin.
+    #[serde(default)]
+    pub alert_hook: Option<String>,
+
+    /// Metrics collected by calling functions on the target nodes.
+    #[serde(default)]
+    pub custom_metrics: Vec<CustomMetric>,
+}
+
+/// A metric whose value is obtained by calling `module:function(args...)` on the target nodes.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomMetric {
+    pub name: String,
+    pub module: String,
+    pub function: String,
+
+    #[serde(default)]
+    pub args: Vec<CallArg>,
+
+    #[serde(rename = "type")]
+    pub kind: CustomMetricKind,
+
+    /// Name of the parent metric (the metric is shown as a root if omitted).
+    #[serde(default)]
+    pub parent: Option<String>,
+
+    /// Path to the value in the result (the result itself is used if empty).
+    #[serde(default)]
+    pub path: Vec<PathElement>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomMetricKind {
+    Gauge,
+    Counter,
+    Utilization,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("failed to parse config file {}", path.display()))
+    }
+}
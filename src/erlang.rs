@@ -1,7 +1,13 @@
 use erl_dist::node::NodeName;
-use erl_dist::term::{Atom, List, Map, Term, Tuple};
+use erl_dist::term::{Atom, FixInteger, List, Map, Pid, Term, Tuple};
+use futures::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const MAX_CONCURRENT_CALLS: usize = 64;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemVersion(String);
@@ -24,6 +30,229 @@ pub fn find_cookie() -> anyhow::Result<String> {
 #[derive(Debug, Clone)]
 pub struct RpcClient {
     handle: erl_rpc::RpcClientHandle,
+    timeout: Option<Duration>,
+
+    // Results of the calls evaluated in a batch by `prefetch()`.
+    prefetched: Arc<Mutex<Vec<(Call, Term)>>>,
+
+    // The shortest round-trip time of the RPCs since the last `take_min_latency()` call.
+    min_latency: Arc<Mutex<Option<Duration>>>,
+
+    // Set to `false` once the connection to the target node is closed.
+    connected: Arc<AtomicBool>,
+}
+
+/// The error returned when an RPC doesn't complete within the timeout set via [`RpcClient::set_timeout()`].
+#[derive(Debug)]
+pub struct RpcTimeoutError {
+    name: String,
+    timeout: Duration,
+}
+
+impl std::fmt::Display for RpcTimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "RPC {} timed out after {:?}", self.name, self.timeout)
+    }
+}
+
+impl std::error::Error for RpcTimeoutError {}
+
+/// A function call that can be evaluated in a batch (see [`RpcClient::prefetch()`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    module: Atom,
+    function: Atom,
+    args: List,
+}
+
+impl Call {
+    /// `Module:Function()`
+    pub fn new(module: &str, function: &str) -> Self {
+        Self::with_args(module, function, Vec::new())
+    }
+
+    /// `erlang:statistics(Item)`
+    pub fn statistics(item: &str) -> Self {
+        Self::with_args("erlang", "statistics", vec![Atom::from(item).into()])
+    }
+
+    /// `erlang:system_info(Item)`
+    pub fn system_info(item: &str) -> Self {
+        Self::with_args("erlang", "system_info", vec![Atom::from(item).into()])
+    }
+
+    /// `erlang:system_info({allocator_sizes, Allocator})`
+    pub fn allocator_sizes(allocator: &str) -> Self {
+        let item = Tuple::from(vec![
+            Atom::from("allocator_sizes").into(),
+            Atom::from(allocator).into(),
+        ]);
+        Self::with_args("erlang", "system_info", vec![item.into()])
+    }
+
+    /// `erlang:system_flag(Name, Value)`
+    pub fn system_flag(name: &str, value: &str) -> Self {
+        Self::with_args(
+            "erlang",
+            "system_flag",
+            vec![Atom::from(name).into(), Atom::from(value).into()],
+        )
+    }
+
+    /// `Module:Function(Args...)` declared in the config file.
+    pub fn from_config(module: &str, function: &str, args: &[CallArg]) -> Self {
+        Self::with_args(
+            module,
+            function,
+            args.iter().map(CallArg::to_term).collect(),
+        )
+    }
+
+    // Only atoms, integers, and tuples and lists of them are allowed as `args`
+    // as they need to be embedded into an expression evaluated on the target node.
+    fn with_args(module: &str, function: &str, args: Vec<Term>) -> Self {
+        Self {
+            module: Atom::from(module),
+            function: Atom::from(function),
+            args: List::from(args),
+        }
+    }
+
+    /// Returns the abstract format of `catch Module:Function(Args...)`.
+    fn to_abstract_expr(&self) -> anyhow::Result<Term> {
+        Ok(abstract_node("catch", vec![self.to_abstract_call()?]))
+    }
+
+    /// Returns the abstract format of `Module:Function(Args...)`.
+    fn to_abstract_call(&self) -> anyhow::Result<Term> {
+        let args = self
+            .args
+            .elements
+            .iter()
+            .map(to_abstract_literal)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(abstract_call(&self.module, &self.function, args))
+    }
+}
+
+/// An argument of a function call declared in the config file.
+///
+/// JSON integers, strings and arrays are converted to Erlang integers, atoms and lists respectively.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CallArg {
+    Integer(i32),
+    Atom(String),
+    List(Vec<CallArg>),
+}
+
+impl CallArg {
+    fn to_term(&self) -> Term {
+        match self {
+            Self::Integer(v) => FixInteger::from(*v).into(),
+            Self::Atom(v) => Atom::from(v.as_str()).into(),
+            Self::List(v) => List::from(v.iter().map(Self::to_term).collect::<Vec<_>>()).into(),
+        }
+    }
+}
+
+/// An element of the path to a value in the result of a function call.
+///
+/// An index (1-origin) selects an element of a tuple or a list,
+/// and a key selects a value of a map or a proplist (keys are atoms).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum PathElement {
+    Index(usize),
+    Key(String),
+}
+
+/// Returns the value at `path` in `term`.
+pub fn term_at_path(mut term: Term, path: &[PathElement]) -> anyhow::Result<Term> {
+    for element in path {
+        term = match (term, element) {
+            (Term::Tuple(tuple), PathElement::Index(i)) => tuple
+                .elements
+                .into_iter()
+                .nth(i.wrapping_sub(1))
+                .ok_or_else(|| anyhow::anyhow!("no element at index {i} of a tuple"))?,
+            (Term::List(list), PathElement::Index(i)) => list
+                .elements
+                .into_iter()
+                .nth(i.wrapping_sub(1))
+                .ok_or_else(|| anyhow::anyhow!("no element at index {i} of a list"))?,
+            (Term::Map(map), PathElement::Key(key)) => map
+                .entries
+                .into_iter()
+                .find(|(k, _)| matches!(k, Term::Atom(atom) if atom.name == *key))
+                .map(|(_, v)| v)
+                .ok_or_else(|| anyhow::anyhow!("no key {key:?} in a map"))?,
+            (Term::List(list), PathElement::Key(key)) => list
+                .elements
+                .into_iter()
+                .filter_map(|x| term_to_tuple(x).ok())
+                .find(|x| {
+                    x.elements.len() == 2
+                        && matches!(&x.elements[0], Term::Atom(atom) if atom.name == *key)
+                })
+                .map(|x| x.elements.into_iter().nth(1).expect("unreachable"))
+                .ok_or_else(|| anyhow::anyhow!("no key {key:?} in a proplist"))?,
+            (term, element) => {
+                anyhow::bail!("cannot select {element:?} from {}", term)
+            }
+        };
+    }
+    Ok(term)
+}
+
+fn abstract_node(tag: &str, elements: Vec<Term>) -> Term {
+    let line = Term::from(FixInteger::from(1));
+    Tuple::from(
+        [Atom::from(tag).into(), line]
+            .into_iter()
+            .chain(elements)
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
+fn abstract_call(module: &Atom, function: &Atom, args: Vec<Term>) -> Term {
+    abstract_node(
+        "call",
+        vec![
+            abstract_node(
+                "remote",
+                vec![
+                    abstract_node("atom", vec![module.clone().into()]),
+                    abstract_node("atom", vec![function.clone().into()]),
+                ],
+            ),
+            List::from(args).into(),
+        ],
+    )
+}
+
+fn to_abstract_literal(term: &Term) -> anyhow::Result<Term> {
+    match term {
+        Term::Atom(_) => Ok(abstract_node("atom", vec![term.clone()])),
+        Term::FixInteger(_) => Ok(abstract_node("integer", vec![term.clone()])),
+        Term::Tuple(tuple) => {
+            let elements = tuple
+                .elements
+                .iter()
+                .map(to_abstract_literal)
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            Ok(abstract_node("tuple", vec![List::from(elements).into()]))
+        }
+        Term::List(list) => list
+            .elements
+            .iter()
+            .rev()
+            .try_fold(abstract_node("nil", Vec::new()), |tail, x| {
+                Ok(abstract_node("cons", vec![to_abstract_literal(x)?, tail]))
+            }),
+        _ => anyhow::bail!("{} cannot be used as an argument of a batched call", term),
+    }
 }
 
 impl RpcClient {
@@ -38,20 +267,203 @@ impl RpcClient {
             erl_rpc::RpcClient::connect(&erlang_node.to_string(), cookie).await?
         };
         let handle = client.handle();
-        smol::spawn(async {
+        let connected = Arc::new(AtomicBool::new(true));
+        let connected_flag = connected.clone();
+        smol::spawn(async move {
             if let Err(e) = client.run().await {
                 log::error!("Erlang RPC Client error: {e}");
             }
+            connected_flag.store(false, Ordering::SeqCst);
         })
         .detach();
 
-        Ok(Self { handle })
+        Ok(Self {
+            handle,
+            timeout: None,
+            prefetched: Arc::new(Mutex::new(Vec::new())),
+            min_latency: Arc::new(Mutex::new(None)),
+            connected,
+        })
+    }
+
+    /// Returns `false` if the connection to the target node has been closed.
+    ///
+    /// Unlike the results of RPCs, this isn't affected by slow or failing calls.
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// Sets the timeout of each RPC (no timeout by default).
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    async fn call_with_timeout(&self, call: Call) -> anyhow::Result<Term> {
+        let Call {
+            module,
+            function,
+            args,
+        } = call;
+        let name = format!("{}:{}/{}", module.name, function.name, args.elements.len());
+        let start = Instant::now();
+        let future = async {
+            let term = self.handle.clone().call(module, function, args).await?;
+            Ok(term)
+        };
+        let result = if let Some(timeout) = self.timeout {
+            smol::future::or(future, async {
+                smol::Timer::after(timeout).await;
+                Err(RpcTimeoutError { name, timeout }.into())
+            })
+            .await
+        } else {
+            future.await
+        };
+        if result.is_ok() {
+            let latency = start.elapsed();
+            let mut min_latency = self.min_latency.lock().expect("unreachable");
+            if min_latency.map_or(true, |x| latency < x) {
+                *min_latency = Some(latency);
+            }
+        }
+        result
+    }
+
+    /// Returns the shortest round-trip time of the RPCs made since the last call of this method.
+    ///
+    /// Calls whose results were prefetched are not counted.
+    pub fn take_min_latency(&self) -> Option<Duration> {
+        self.min_latency.lock().expect("unreachable").take()
+    }
+
+    async fn call(&self, module: Atom, function: Atom, args: List) -> anyhow::Result<Term> {
+        let call = Call {
+            module,
+            function,
+            args,
+        };
+        {
+            let mut prefetched = self.prefetched.lock().expect("unreachable");
+            if let Some(i) = prefetched.iter().position(|(x, _)| *x == call) {
+                return Ok(prefetched.swap_remove(i).1);
+            }
+        }
+        self.call_with_timeout(call).await
+    }
+
+    /// Evaluates the given calls on the target node in a single round trip via `erl_eval`.
+    ///
+    /// The results are consumed by the subsequent identical calls instead of issuing new RPCs.
+    /// Calls that raised an exception are not prefetched, so they will be retried individually.
+    pub async fn prefetch(&self, calls: Vec<Call>) -> anyhow::Result<()> {
+        let exprs = calls
+            .iter()
+            .map(Call::to_abstract_expr)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let expr = abstract_node("tuple", vec![List::from(exprs).into()]);
+        let values = term_to_tuple(self.eval(expr).await?)?;
+        anyhow::ensure!(
+            values.elements.len() == calls.len(),
+            "expected a {}-elements tuple, but got {}",
+            calls.len(),
+            values
+        );
+
+        let mut prefetched = self.prefetched.lock().expect("unreachable");
+        prefetched.clear();
+        for (call, value) in calls.into_iter().zip(values.elements) {
+            if is_exit(&value) {
+                continue;
+            }
+            prefetched.push((call, value));
+        }
+        Ok(())
+    }
+
+    /// Evaluates the given expression (in the abstract format) on the target node via `erl_eval`.
+    async fn eval(&self, expr: Term) -> anyhow::Result<Term> {
+        let term = self
+            .call_with_timeout(Call::with_args(
+                "erl_eval",
+                "expr",
+                vec![expr, List::nil().into()],
+            ))
+            .await?;
+
+        // `{value, Value, Bindings}`
+        let tuple = term_to_tuple(term)?;
+        anyhow::ensure!(
+            tuple.elements.len() == 3,
+            "expected a three-elements tuple, but got {}",
+            tuple
+        );
+        Ok(tuple.elements.into_iter().nth(1).expect("unreachable"))
+    }
+
+    /// Evaluates `[{X, catch M1:F1(X, Args1...), catch M2:F2(X, Args2...), ...} || X <- Generator]`
+    /// on the target node in a single round trip.
+    ///
+    /// `X` is prepended to the arguments of each of `calls` (which must be literals).
+    /// A call that raised an exception results in `{'EXIT', Reason}` without affecting the other elements.
+    async fn call_for_each(
+        &self,
+        generator: Call,
+        calls: Vec<Call>,
+    ) -> anyhow::Result<Vec<(Term, Vec<Term>)>> {
+        self.call_for_each_in(generator.to_abstract_call()?, calls)
+            .await
+    }
+
+    /// Same as [`RpcClient::call_for_each()`] but `generator` is an expression in the abstract format.
+    async fn call_for_each_in(
+        &self,
+        generator: Term,
+        calls: Vec<Call>,
+    ) -> anyhow::Result<Vec<(Term, Vec<Term>)>> {
+        let x = abstract_node("var", vec![Atom::from("X").into()]);
+        let mut elements = vec![x.clone()];
+        for call in calls.iter() {
+            let mut args = vec![x.clone()];
+            for arg in &call.args.elements {
+                args.push(to_abstract_literal(arg)?);
+            }
+            elements.push(abstract_node(
+                "catch",
+                vec![abstract_call(&call.module, &call.function, args)],
+            ));
+        }
+        let expr = abstract_node(
+            "lc",
+            vec![
+                abstract_node("tuple", vec![List::from(elements).into()]),
+                List::from(vec![abstract_node("generate", vec![x, generator])]).into(),
+            ],
+        );
+        term_to_list(self.eval(expr).await?)?
+            .elements
+            .into_iter()
+            .map(|x| {
+                let tuple = term_to_tuple(x)?;
+                anyhow::ensure!(
+                    tuple.elements.len() == calls.len() + 1,
+                    "expected a {}-elements tuple, but got {}",
+                    calls.len() + 1,
+                    tuple
+                );
+                let mut elements = tuple.elements.into_iter();
+                let x = elements.next().expect("unreachable");
+                Ok((x, elements.collect()))
+            })
+            .collect()
+    }
+
+    /// Discards the prefetched results that have not been consumed.
+    pub fn clear_prefetched(&self) {
+        self.prefetched.lock().expect("unreachable").clear();
     }
 
     pub async fn get_system_version(&self) -> anyhow::Result<SystemVersion> {
         let term = self
-            .handle
-            .clone()
             .call(
                 "erlang".into(),
                 "system_info".into(),
@@ -63,8 +475,6 @@ impl RpcClient {
 
     pub async fn get_system_info_u64(&self, item_name: &str) -> anyhow::Result<u64> {
         let term = self
-            .handle
-            .clone()
             .call(
                 "erlang".into(),
                 "system_info".into(),
@@ -92,6 +502,20 @@ impl RpcClient {
         Ok((in_bytes, out_bytes))
     }
 
+    /// Returns the number of garbage collections and words reclaimed.
+    pub async fn get_statistics_garbage_collection(&self) -> anyhow::Result<(u64, u64)> {
+        let term = self.get_statistics("garbage_collection").await?;
+        let tuple = term_to_tuple(term)?;
+        anyhow::ensure!(
+            tuple.elements.len() >= 2,
+            "expected a tuple having 2 or more elements, but got {}",
+            tuple
+        );
+        let count = term_to_u64(tuple.elements[0].clone())?;
+        let words_reclaimed = term_to_u64(tuple.elements[1].clone())?;
+        Ok((count, words_reclaimed))
+    }
+
     pub async fn get_statistics_microstate_accounting(&self) -> anyhow::Result<Vec<MSAccThread>> {
         let term = self.get_statistics("microstate_accounting").await?;
         term_to_list(term)?
@@ -101,10 +525,83 @@ impl RpcClient {
             .collect()
     }
 
+    /// Returns `Ok(None)` if the `scheduler_wall_time` system flag is disabled.
+    pub async fn get_statistics_scheduler_wall_time(
+        &self,
+    ) -> anyhow::Result<Option<Vec<SchedulerWallTime>>> {
+        let term = self.get_statistics("scheduler_wall_time_all").await?;
+        if is_undefined(&term) {
+            return Ok(None);
+        }
+        term_to_list(term)?
+            .elements
+            .into_iter()
+            .map(SchedulerWallTime::from_term)
+            .collect::<anyhow::Result<_>>()
+            .map(Some)
+    }
+
+    /// Calls a function declared in the config file.
+    pub async fn call_mfa(&self, call: &Call) -> anyhow::Result<Term> {
+        self.call(
+            call.module.clone(),
+            call.function.clone(),
+            call.args.clone(),
+        )
+        .await
+    }
+
+    /// Returns the block and carrier sizes of each instance of the `alloc_util` allocators.
+    pub async fn get_alloc_util_allocators(&self) -> anyhow::Result<Vec<String>> {
+        let term = self
+            .call(
+                "erlang".into(),
+                "system_info".into(),
+                List::from(vec![Atom::from("alloc_util_allocators").into()]),
+            )
+            .await?;
+        term_to_list(term)?
+            .elements
+            .into_iter()
+            .map(|x| term_to_atom(x).map(|x| x.name))
+            .collect()
+    }
+
+    pub async fn get_allocator_sizes(&self) -> anyhow::Result<Vec<AllocatorSizes>> {
+        let allocators = futures::stream::iter(self.get_alloc_util_allocators().await?)
+            .map(|allocator| self.get_allocator_sizes_of(allocator))
+            .buffer_unordered(MAX_CONCURRENT_CALLS)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(allocators.into_iter().flatten().collect())
+    }
+
+    async fn get_allocator_sizes_of(&self, name: String) -> anyhow::Result<Vec<AllocatorSizes>> {
+        let allocator = Term::from(Atom::from(name.as_str()));
+        let term = self
+            .call(
+                "erlang".into(),
+                "system_info".into(),
+                List::from(vec![Tuple::from(vec![
+                    Atom::from("allocator_sizes").into(),
+                    allocator,
+                ])
+                .into()]),
+            )
+            .await?;
+        if matches!(&term, Term::Atom(atom) if atom.name == "false") {
+            // The allocator is disabled.
+            return Ok(Vec::new());
+        }
+        term_to_list(term)?
+            .elements
+            .into_iter()
+            .map(|instance| AllocatorSizes::from_term(&name, instance))
+            .collect()
+    }
+
     pub async fn set_system_flag_bool(&self, name: &str, value: &str) -> anyhow::Result<bool> {
         let term = self
-            .handle
-            .clone()
             .call(
                 "erlang".into(),
                 "system_flag".into(),
@@ -114,10 +611,111 @@ impl RpcClient {
         term_to_bool(term)
     }
 
+    /// Spawns a process on the target node that enables the given boolean system flag and keeps it enabled
+    /// until the process is killed via [`RpcClient::kill_process()`] or the connection to `erldash` is lost.
+    ///
+    /// This is needed for flags such as `scheduler_wall_time` that are reference counted per process:
+    /// such a flag is disabled again as soon as the (short-lived) RPC worker process that enabled it exits.
+    /// Returns the PID of the spawned process and the old state of the flag.
+    pub async fn spawn_system_flag_keeper(&self, name: &str) -> anyhow::Result<(Pid, bool)> {
+        let atom = |name: &str| abstract_node("atom", vec![Atom::from(name).into()]);
+        let var = |name: &str| abstract_node("var", vec![Atom::from(name).into()]);
+        let call = |function: &str, args: Vec<Term>| {
+            abstract_call(&Atom::from("erlang"), &Atom::from(function), args)
+        };
+        let tuple = |elements: Vec<Term>| abstract_node("tuple", vec![List::from(elements).into()]);
+        let receive = |pattern: Term, body: Term| {
+            let clause = abstract_node(
+                "clause",
+                vec![
+                    List::from(vec![pattern]).into(),
+                    List::nil().into(),
+                    List::from(vec![body]).into(),
+                ],
+            );
+            abstract_node("receive", vec![List::from(vec![clause]).into()])
+        };
+
+        // The group leader of an RPC worker process is a process on the `erldash` node.
+        let keeper_body = vec![
+            abstract_node(
+                "catch",
+                vec![call(
+                    "monitor_node",
+                    vec![
+                        call("node", vec![call("group_leader", Vec::new())]),
+                        atom("true"),
+                    ],
+                )],
+            ),
+            abstract_node(
+                "op",
+                vec![
+                    Atom::from("!").into(),
+                    var("Parent"),
+                    tuple(vec![
+                        call("self", Vec::new()),
+                        call("system_flag", vec![atom(name), atom("true")]),
+                    ]),
+                ],
+            ),
+            receive(tuple(vec![atom("nodedown"), var("_")]), atom("ok")),
+        ];
+        let keeper = abstract_node(
+            "fun",
+            vec![Tuple::from(vec![
+                Atom::from("clauses").into(),
+                List::from(vec![abstract_node(
+                    "clause",
+                    vec![
+                        List::nil().into(),
+                        List::nil().into(),
+                        List::from(keeper_body).into(),
+                    ],
+                )])
+                .into(),
+            ])
+            .into()],
+        );
+
+        // Waits for the flag to be enabled so that the next poll sees it.
+        let expr = abstract_node(
+            "block",
+            vec![List::from(vec![
+                abstract_node("match", vec![var("Parent"), call("self", Vec::new())]),
+                abstract_node("match", vec![var("Keeper"), call("spawn", vec![keeper])]),
+                receive(
+                    tuple(vec![var("Keeper"), var("Old")]),
+                    tuple(vec![var("Keeper"), var("Old")]),
+                ),
+            ])
+            .into()],
+        );
+
+        let tuple = term_to_tuple(self.eval(expr).await?)?;
+        anyhow::ensure!(
+            tuple.elements.len() == 2,
+            "expected a two-elements tuple, but got {}",
+            tuple
+        );
+        let mut elements = tuple.elements.into_iter();
+        let pid = term_to_pid(elements.next().expect("unreachable"))?;
+        let old = term_to_bool(elements.next().expect("unreachable"))?;
+        Ok((pid, old))
+    }
+
+    pub async fn kill_process(&self, pid: Pid) -> anyhow::Result<()> {
+        self.call(
+            "erlang".into(),
+            "exit".into(),
+            List::from(vec![pid.into(), Atom::from("kill").into()]),
+        )
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_memory(&self) -> anyhow::Result<BTreeMap<String, u64>> {
         let term = self
-            .handle
-            .clone()
             .call("erlang".into(), "memory".into(), List::nil())
             .await?;
         term_to_list(term)?
@@ -137,10 +735,359 @@ impl RpcClient {
             .collect()
     }
 
+    pub async fn get_nodes(&self) -> anyhow::Result<Vec<String>> {
+        let term = self
+            .call("erlang".into(), "nodes".into(), List::nil())
+            .await?;
+        term_to_list(term)?
+            .elements
+            .into_iter()
+            .map(|x| term_to_atom(x).map(|x| x.name))
+            .collect()
+    }
+
+    /// Returns the information of all processes (fetched in a single round trip).
+    pub async fn get_process_info_all(&self) -> anyhow::Result<Vec<ProcessInfo>> {
+        let items = ProcessInfo::ITEMS
+            .iter()
+            .map(|item| Term::from(Atom::from(*item)))
+            .collect::<Vec<_>>();
+        let processes = self
+            .call_for_each(
+                Call::new("erlang", "processes"),
+                vec![Call::with_args(
+                    "erlang",
+                    "process_info",
+                    vec![List::from(items).into()],
+                )],
+            )
+            .await?;
+
+        // Processes that have already terminated are omitted.
+        processes
+            .into_iter()
+            .filter_map(|(pid, mut info)| info.pop().map(|info| (pid, info)))
+            .filter(|(_, info)| !is_undefined(info) && !is_exit(info))
+            .map(|(pid, info)| ProcessInfo::from_term(term_to_pid(pid)?, info))
+            .collect()
+    }
+
+    /// Returns `Ok(None)` if the process has already terminated.
+    pub async fn get_process_detail(&self, pid: Pid) -> anyhow::Result<Option<ProcessDetail>> {
+        let term = self
+            .call(
+                "erlang".into(),
+                "process_info".into(),
+                List::from(vec![pid.clone().into()]),
+            )
+            .await?;
+        if is_undefined(&term) {
+            return Ok(None);
+        }
+        let mut items = term_to_key_value_list(term)?;
+
+        // `erlang:process_info/1` omits some useful items, so they are fetched separately.
+        let extra_items = ProcessDetail::EXTRA_ITEMS
+            .iter()
+            .map(|item| Term::from(Atom::from(*item)))
+            .collect::<Vec<_>>();
+        let term = self
+            .call(
+                "erlang".into(),
+                "process_info".into(),
+                List::from(vec![pid.clone().into(), List::from(extra_items).into()]),
+            )
+            .await?;
+        if is_undefined(&term) {
+            return Ok(None);
+        }
+        items.extend(term_to_key_value_list(term)?);
+
+        Ok(Some(ProcessDetail { pid, items }))
+    }
+
+    /// Returns the information of all ETS tables (fetched in a single round trip).
+    pub async fn get_ets_tables(&self) -> anyhow::Result<Vec<EtsTableInfo>> {
+        let tables = self
+            .call_for_each(
+                Call::new("ets", "all"),
+                vec![Call::with_args("ets", "info", Vec::new())],
+            )
+            .await?;
+
+        // Tables that have already been deleted are omitted.
+        tables
+            .into_iter()
+            .filter_map(|(table, mut info)| info.pop().map(|info| (table, info)))
+            .filter(|(_, info)| !is_undefined(info) && !is_exit(info))
+            .map(|(table, info)| EtsTableInfo::from_term(table, info))
+            .collect()
+    }
+
+    /// Returns the number of objects and the memory of each ETS table (lighter than [`RpcClient::get_ets_tables()`]).
+    pub async fn get_ets_table_sizes(&self) -> anyhow::Result<Vec<EtsTableSize>> {
+        let tables = self
+            .call_for_each(
+                Call::new("ets", "all"),
+                vec![
+                    Call::with_args("ets", "info", vec![Atom::from("name").into()]),
+                    Call::with_args("ets", "info", vec![Atom::from("size").into()]),
+                    Call::with_args("ets", "info", vec![Atom::from("memory").into()]),
+                ],
+            )
+            .await?;
+        let mut sizes = Vec::with_capacity(tables.len());
+        for (table, values) in tables {
+            // Tables that have already been deleted are omitted.
+            if values.iter().any(|x| is_undefined(x) || is_exit(x)) {
+                continue;
+            }
+            let mut values = values.into_iter();
+            let name = term_to_atom(values.next().expect("unreachable"))?.name;
+            sizes.push(EtsTableSize {
+                id: ets_table_id(&table, &name),
+                size: term_to_u64(values.next().expect("unreachable"))?,
+                memory: term_to_u64(values.next().expect("unreachable"))?,
+            });
+        }
+        Ok(sizes)
+    }
+
+    pub async fn get_port_info_all(&self) -> anyhow::Result<Vec<PortInfo>> {
+        let ports = self
+            .call_for_each(
+                Call::new("erlang", "ports"),
+                vec![
+                    Call::with_args("erlang", "port_info", Vec::new()),
+                    Call::with_args("erlang", "port_info", vec![Atom::from("queue_size").into()]),
+                ],
+            )
+            .await?;
+        let mut socket_stats = self.get_socket_stats_all().await?;
+
+        let mut infos = Vec::with_capacity(ports.len());
+        for (port, values) in ports {
+            // Ports that have already been closed are omitted.
+            if values.iter().any(|x| is_undefined(x) || is_exit(x)) {
+                continue;
+            }
+            let mut values = values.into_iter();
+            let mut info = PortInfo::from_term(&port, values.next().expect("unreachable"))?;
+            info.queue_size = term_to_tuple_2nd_u64(values.next().expect("unreachable"))?;
+            info.socket_stats = socket_stats.remove(&info.id);
+            infos.push(info);
+        }
+        Ok(infos)
+    }
+
+    /// Returns the `inet:getstat/1` statistics of the sockets keyed by their port IDs.
+    ///
+    /// `inet:getstat/1` is only called for the ports of [`PortInfo::SOCKET_DRIVERS`],
+    /// i.e., `[P || P <- erlang:ports(), {name, N} <- [erlang:port_info(P, name)], lists:member(N, Drivers)]`.
+    async fn get_socket_stats_all(
+        &self,
+    ) -> anyhow::Result<BTreeMap<String, BTreeMap<String, u64>>> {
+        let var = |name: &str| abstract_node("var", vec![Atom::from(name).into()]);
+        let drivers = List::from(
+            PortInfo::SOCKET_DRIVERS
+                .iter()
+                .map(|driver| {
+                    List::from(
+                        driver
+                            .bytes()
+                            .map(|b| Term::from(FixInteger::from(i32::from(b))))
+                            .collect::<Vec<_>>(),
+                    )
+                    .into()
+                })
+                .collect::<Vec<Term>>(),
+        );
+        let name_info = abstract_node(
+            "tuple",
+            vec![List::from(vec![
+                abstract_node("atom", vec![Atom::from("name").into()]),
+                var("N"),
+            ])
+            .into()],
+        );
+        let sockets = abstract_node(
+            "lc",
+            vec![
+                var("P"),
+                List::from(vec![
+                    abstract_node(
+                        "generate",
+                        vec![var("P"), Call::new("erlang", "ports").to_abstract_call()?],
+                    ),
+                    abstract_node(
+                        "generate",
+                        vec![
+                            name_info,
+                            abstract_node(
+                                "cons",
+                                vec![
+                                    abstract_call(
+                                        &Atom::from("erlang"),
+                                        &Atom::from("port_info"),
+                                        vec![
+                                            var("P"),
+                                            abstract_node("atom", vec![Atom::from("name").into()]),
+                                        ],
+                                    ),
+                                    abstract_node("nil", Vec::new()),
+                                ],
+                            ),
+                        ],
+                    ),
+                    abstract_call(
+                        &Atom::from("lists"),
+                        &Atom::from("member"),
+                        vec![var("N"), to_abstract_literal(&drivers.into())?],
+                    ),
+                ])
+                .into(),
+            ],
+        );
+
+        let sockets = self
+            .call_for_each_in(
+                sockets,
+                vec![Call::with_args("inet", "getstat", Vec::new())],
+            )
+            .await?;
+        let mut stats = BTreeMap::new();
+        for (port, mut values) in sockets {
+            // Sockets closed in the meantime are omitted.
+            if let Some(x) = term_to_inet_stats(values.pop().expect("unreachable"))? {
+                stats.insert(format_term(&port), x);
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Returns `Ok(None)` if the port has already been closed.
+    pub async fn get_port_info(&self, port: Term) -> anyhow::Result<Option<PortInfo>> {
+        let term = self
+            .call(
+                "erlang".into(),
+                "port_info".into(),
+                List::from(vec![port.clone()]),
+            )
+            .await?;
+        if is_undefined(&term) {
+            return Ok(None);
+        }
+        let mut info = PortInfo::from_term(&port, term)?;
+
+        let term = self
+            .call(
+                "erlang".into(),
+                "port_info".into(),
+                List::from(vec![port.clone(), Atom::from("queue_size").into()]),
+            )
+            .await?;
+        if is_undefined(&term) {
+            return Ok(None);
+        }
+        info.queue_size = term_to_tuple_2nd_u64(term)?;
+
+        if PortInfo::SOCKET_DRIVERS.contains(&info.name.as_str()) {
+            info.socket_stats = self.get_inet_stats(port).await?;
+        }
+        Ok(Some(info))
+    }
+
+    /// Returns the connections to the other nodes (i.e., `erlang:system_info(dist_ctrl)`).
+    pub async fn get_dist_connections(&self) -> anyhow::Result<Vec<DistConnection>> {
+        let term = self
+            .call(
+                "erlang".into(),
+                "system_info".into(),
+                List::from(vec![Atom::from("dist_ctrl").into()]),
+            )
+            .await?;
+        let mut packet_counts = self.get_dist_packet_counts().await?;
+        let mut connections = futures::stream::iter(term_to_list(term)?.elements)
+            .map(|x| self.get_dist_connection(x))
+            .buffer_unordered(MAX_CONCURRENT_CALLS)
+            .try_collect::<Vec<_>>()
+            .await?;
+        for connection in &mut connections {
+            connection.packet_counts = packet_counts.remove(&connection.node);
+        }
+        Ok(connections)
+    }
+
+    /// Returns the numbers of the packets received and sent via the connection to each node.
+    ///
+    /// The distribution handle that `erlang:dist_get_stat/1` takes is only available to the process owning
+    /// the connection, so the counters are obtained via `net_kernel:node_info/1`, which asks the owner
+    /// to call `erlang:dist_get_stat/1` (or `inet:getstat/2` for the TCP distribution).
+    /// Unlike the port statistics, this also works for the connections controlled by processes (e.g., TLS).
+    async fn get_dist_packet_counts(&self) -> anyhow::Result<BTreeMap<String, (u64, u64)>> {
+        let nodes = self
+            .call_for_each(
+                Call::with_args("erlang", "nodes", vec![Atom::from("connected").into()]),
+                vec![Call::with_args("net_kernel", "node_info", Vec::new())],
+            )
+            .await?;
+        let mut counts = BTreeMap::new();
+        for (node, mut values) in nodes {
+            // `{error, bogus}` (or an exception) is returned if the connection has already been closed.
+            let tuple = term_to_tuple(values.pop().expect("unreachable"))?;
+            if tuple.elements.len() != 2
+                || !matches!(&tuple.elements[0], Term::Atom(x) if x.name == "ok")
+            {
+                continue;
+            }
+            let info = term_to_key_value_list(tuple.elements[1].clone())?;
+            let count = |key: &str| {
+                info.iter()
+                    .find(|(k, _)| k == key)
+                    .and_then(|(_, v)| term_to_u64(v.clone()).ok())
+            };
+            if let (Some(input), Some(output)) = (count("in"), count("out")) {
+                counts.insert(term_to_atom(node)?.name, (input, output));
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn get_dist_connection(&self, term: Term) -> anyhow::Result<DistConnection> {
+        let tuple = term_to_tuple(term)?;
+        anyhow::ensure!(
+            tuple.elements.len() == 2,
+            "expected a two-elements tuple, but got {}",
+            tuple
+        );
+        let mut elements = tuple.elements.into_iter();
+        let node = term_to_atom(elements.next().expect("unreachable"))?.name;
+        let controller = elements.next().expect("unreachable");
+
+        // Distribution carriers other than TCP (e.g., TLS) may use a process as the controller.
+        let port = if matches!(controller, Term::Port(_)) {
+            self.get_port_info(controller.clone()).await?
+        } else {
+            None
+        };
+        Ok(DistConnection {
+            node,
+            controller: format_term(&controller),
+            port,
+            packet_counts: None,
+        })
+    }
+
+    /// Returns `Ok(None)` if `inet:getstat/1` failed (e.g., the socket has already been closed).
+    async fn get_inet_stats(&self, port: Term) -> anyhow::Result<Option<BTreeMap<String, u64>>> {
+        let term = self
+            .call("inet".into(), "getstat".into(), List::from(vec![port]))
+            .await?;
+        term_to_inet_stats(term)
+    }
+
     async fn get_statistics(&self, item_name: &str) -> anyhow::Result<Term> {
         let term = self
-            .handle
-            .clone()
             .call(
                 "erlang".into(),
                 "statistics".into(),
@@ -151,6 +1098,25 @@ impl RpcClient {
     }
 }
 
+/// Converts a result of `inet:getstat/1` (`Ok(None)` if it's an error such as `{error, einval}` or `{'EXIT', _}`).
+fn term_to_inet_stats(term: Term) -> anyhow::Result<Option<BTreeMap<String, u64>>> {
+    let tuple = term_to_tuple(term)?;
+    anyhow::ensure!(
+        tuple.elements.len() == 2,
+        "expected a two-elements tuple, but got {}",
+        tuple
+    );
+    let mut elements = tuple.elements.into_iter();
+    if term_to_atom(elements.next().expect("unreachable"))?.name != "ok" {
+        return Ok(None);
+    }
+    term_to_key_value_list(elements.next().expect("unreachable"))?
+        .into_iter()
+        .map(|(k, v)| Ok((k, term_to_u64(v)?)))
+        .collect::<anyhow::Result<_>>()
+        .map(Some)
+}
+
 fn term_to_tuple_1st_u64(term: Term) -> anyhow::Result<u64> {
     let tuple = term_to_tuple(term)?;
     anyhow::ensure!(
@@ -171,7 +1137,14 @@ fn term_to_tuple_2nd_u64(term: Term) -> anyhow::Result<u64> {
     term_to_u64(tuple.elements[1].clone())
 }
 
-fn term_to_u64(term: Term) -> anyhow::Result<u64> {
+pub fn term_to_f64(term: Term) -> anyhow::Result<f64> {
+    match term {
+        Term::Float(v) => Ok(v.value),
+        v => term_to_u64(v).map(|v| v as f64),
+    }
+}
+
+pub fn term_to_u64(term: Term) -> anyhow::Result<u64> {
     let v = match term {
         Term::FixInteger(v) => v.value.try_into()?,
         Term::BigInteger(v) => v.value.try_into()?,
@@ -219,6 +1192,52 @@ fn term_to_atom(term: Term) -> anyhow::Result<Atom> {
         .map_err(|x| anyhow::anyhow!("expected an atom, but got {x}"))
 }
 
+fn term_to_pid(term: Term) -> anyhow::Result<Pid> {
+    if let Term::Pid(pid) = term {
+        Ok(pid)
+    } else {
+        anyhow::bail!("expected a pid, but got {}", term)
+    }
+}
+
+fn term_to_mfa_string(term: Term) -> String {
+    if let Term::Tuple(tuple) = &term {
+        if let [Term::Atom(m), Term::Atom(f), Term::FixInteger(a)] = tuple.elements.as_slice() {
+            return format!("{}:{}/{}", m.name, f.name, a.value);
+        }
+    }
+    term.to_string()
+}
+
+fn term_to_key_value_list(term: Term) -> anyhow::Result<Vec<(String, Term)>> {
+    term_to_list(term)?
+        .elements
+        .into_iter()
+        .map(|x| {
+            let tuple = term_to_tuple(x)?;
+            anyhow::ensure!(
+                tuple.elements.len() == 2,
+                "expected a two-elements tuple, but got {}",
+                tuple
+            );
+            let mut elements = tuple.elements.into_iter();
+            let key = term_to_atom(elements.next().expect("unreachable"))?;
+            let value = elements.next().expect("unreachable");
+            Ok((key.name, value))
+        })
+        .collect()
+}
+
+/// Returns `true` if `term` is `{'EXIT', Reason}` (i.e., the result of `catch` for an exception).
+fn is_exit(term: &Term) -> bool {
+    matches!(term, Term::Tuple(tuple)
+        if matches!(tuple.elements.first(), Some(Term::Atom(atom)) if atom.name == "EXIT"))
+}
+
+fn is_undefined(term: &Term) -> bool {
+    matches!(term, Term::Atom(atom) if atom.name == "undefined")
+}
+
 fn term_to_tuple(term: Term) -> anyhow::Result<Tuple> {
     term.try_into()
         .map_err(|x| anyhow::anyhow!("expected a tuple, but got {x}"))
@@ -272,3 +1291,428 @@ impl MSAccThread {
         })
     }
 }
+
+/// An element of `erlang:statistics(scheduler_wall_time_all)`.
+#[derive(Debug, Clone)]
+pub struct SchedulerWallTime {
+    pub scheduler_id: u64,
+    pub active_time: u64,
+    pub total_time: u64,
+}
+
+impl SchedulerWallTime {
+    fn from_term(term: Term) -> anyhow::Result<Self> {
+        let tuple = term_to_tuple(term)?;
+        anyhow::ensure!(
+            tuple.elements.len() == 3,
+            "expected a three-elements tuple, but got {}",
+            tuple
+        );
+        let mut elements = tuple.elements.into_iter();
+        Ok(Self {
+            scheduler_id: term_to_u64(elements.next().expect("unreachable"))?,
+            active_time: term_to_u64(elements.next().expect("unreachable"))?,
+            total_time: term_to_u64(elements.next().expect("unreachable"))?,
+        })
+    }
+}
+
+/// An instance of an allocator in `erlang:system_info({allocator_sizes, Alloc})`.
+#[derive(Debug, Clone)]
+pub struct AllocatorSizes {
+    pub allocator: String,
+    pub instance: u64,
+    pub blocks_bytes: u64,
+    pub carriers_bytes: u64,
+}
+
+impl AllocatorSizes {
+    fn from_term(allocator: &str, term: Term) -> anyhow::Result<Self> {
+        let tuple = term_to_tuple(term)?;
+        anyhow::ensure!(
+            tuple.elements.len() == 3,
+            "expected a three-elements tuple, but got {}",
+            tuple
+        );
+        let mut elements = tuple.elements.into_iter().skip(1);
+        let instance = term_to_u64(elements.next().expect("unreachable"))?;
+        let mut this = Self {
+            allocator: allocator.to_owned(),
+            instance,
+            blocks_bytes: 0,
+            carriers_bytes: 0,
+        };
+
+        // Each item is a pair of a carrier type (`mbcs`, `mbcs_pool` or `sbcs`) and its sizes.
+        for item in term_to_list(elements.next().expect("unreachable"))?.elements {
+            let tuple = term_to_tuple(item)?;
+            let Some(Term::List(sizes)) = tuple.elements.get(1) else {
+                continue;
+            };
+            for size in &sizes.elements {
+                this.add_size(size.clone())?;
+            }
+        }
+        Ok(this)
+    }
+
+    fn add_size(&mut self, term: Term) -> anyhow::Result<()> {
+        let tuple = term_to_tuple(term)?;
+        anyhow::ensure!(
+            tuple.elements.len() >= 2,
+            "expected a tuple having 2 or more elements, but got {}",
+            tuple
+        );
+        let key = term_to_atom(tuple.elements[0].clone())?;
+        match key.name.as_str() {
+            "carriers_size" => {
+                self.carriers_bytes += term_to_u64(tuple.elements[1].clone())?;
+            }
+            // The total size of the blocks (reported by older releases).
+            "blocks_size" => {
+                self.blocks_bytes += term_to_u64(tuple.elements[1].clone())?;
+            }
+            // The sizes of the blocks per allocation type (reported by newer releases instead of `blocks_size`).
+            "blocks" => {
+                if let Term::List(blocks) = &tuple.elements[1] {
+                    for block in &blocks.elements {
+                        let tuple = term_to_tuple(block.clone())?;
+                        let Some(Term::List(items)) = tuple.elements.get(1) else {
+                            continue;
+                        };
+                        for item in &items.elements {
+                            let item = term_to_tuple(item.clone())?;
+                            if matches!(item.elements.first(), Some(Term::Atom(x)) if x.name == "size")
+                            {
+                                self.blocks_bytes += term_to_tuple_2nd_u64(item.into())?;
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// A connection to another node.
+#[derive(Debug, Clone)]
+pub struct DistConnection {
+    pub node: String,
+
+    /// The port or process controlling the connection.
+    pub controller: String,
+
+    /// `None` if the controller is not a port.
+    pub port: Option<PortInfo>,
+
+    /// The numbers of the received and sent packets (`None` if unavailable).
+    pub packet_counts: Option<(u64, u64)>,
+}
+
+/// Renders a term in a (mostly) Erlang-like syntax.
+pub fn format_term(term: &Term) -> String {
+    match term {
+        Term::List(list) => {
+            if let Some(s) = to_printable_string(list) {
+                return format!("{s:?}");
+            }
+            format!("[{}]", format_terms(&list.elements))
+        }
+        Term::ImproperList(list) => {
+            format!(
+                "[{}|{}]",
+                format_terms(&list.elements),
+                format_term(&list.last)
+            )
+        }
+        Term::Tuple(tuple) => format!("{{{}}}", format_terms(&tuple.elements)),
+        Term::Map(map) => {
+            let entries = map
+                .entries
+                .iter()
+                .map(|(k, v)| format!("{} => {}", format_term(k), format_term(v)))
+                .collect::<Vec<_>>();
+            format!("#{{{}}}", entries.join(","))
+        }
+        Term::Binary(binary) => {
+            const MAX_BYTES: usize = 64;
+            let bytes = &binary.bytes[..std::cmp::min(binary.bytes.len(), MAX_BYTES)];
+            let ellipsis = if bytes.len() < binary.bytes.len() {
+                "..."
+            } else {
+                ""
+            };
+            match std::str::from_utf8(bytes) {
+                Ok(s) if !s.chars().any(|c| c.is_control()) => format!("<<{s:?}{ellipsis}>>"),
+                _ => {
+                    let bytes = bytes.iter().map(|b| b.to_string()).collect::<Vec<_>>();
+                    format!("<<{}{ellipsis}>>", bytes.join(","))
+                }
+            }
+        }
+        term => term.to_string(),
+    }
+}
+
+fn format_terms(terms: &[Term]) -> String {
+    terms.iter().map(format_term).collect::<Vec<_>>().join(",")
+}
+
+fn to_printable_string(list: &List) -> Option<String> {
+    if list.elements.is_empty() {
+        return None;
+    }
+    list.elements
+        .iter()
+        .map(|x| match x {
+            Term::FixInteger(v) => u8::try_from(v.value)
+                .ok()
+                .filter(|b| b.is_ascii_graphic() || *b == b' ')
+                .map(char::from),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessDetail {
+    pub pid: Pid,
+    pub items: Vec<(String, Term)>,
+}
+
+impl ProcessDetail {
+    const EXTRA_ITEMS: &'static [&'static str] =
+        &["monitors", "monitored_by", "current_stacktrace", "memory"];
+
+    /// Returns pairs of an item name and a rendered value.
+    ///
+    /// List values are split into one row per element, and only keys are shown for the process dictionary.
+    pub fn rows(&self) -> Vec<(&str, String)> {
+        let mut rows = Vec::new();
+        for (key, value) in &self.items {
+            match value {
+                Term::List(list) if key == "dictionary" => {
+                    for entry in &list.elements {
+                        let k = match entry {
+                            Term::Tuple(tuple) if tuple.elements.len() == 2 => &tuple.elements[0],
+                            entry => entry,
+                        };
+                        rows.push((key.as_str(), format_term(k)));
+                    }
+                }
+                Term::List(list)
+                    if !list.elements.is_empty() && to_printable_string(list).is_none() =>
+                {
+                    for element in &list.elements {
+                        rows.push((key.as_str(), format_term(element)));
+                    }
+                }
+                _ => {
+                    rows.push((key.as_str(), format_term(value)));
+                }
+            }
+        }
+        rows
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: Pid,
+    pub registered_name: Option<String>,
+    pub initial_call: String,
+    pub current_function: String,
+    pub reductions: u64,
+    pub memory: u64,
+    pub message_queue_len: u64,
+    pub total_heap_size: u64, // in words
+}
+
+impl ProcessInfo {
+    const ITEMS: &'static [&'static str] = &[
+        "registered_name",
+        "initial_call",
+        "current_function",
+        "reductions",
+        "memory",
+        "message_queue_len",
+        "total_heap_size",
+    ];
+
+    fn from_term(pid: Pid, term: Term) -> anyhow::Result<Self> {
+        let mut info = Self {
+            pid,
+            registered_name: None,
+            initial_call: String::new(),
+            current_function: String::new(),
+            reductions: 0,
+            memory: 0,
+            message_queue_len: 0,
+            total_heap_size: 0,
+        };
+        for (key, value) in term_to_key_value_list(term)? {
+            match key.as_str() {
+                "registered_name" => {
+                    // An empty list is returned if the process has no registered name.
+                    info.registered_name = term_to_atom(value).ok().map(|x| x.name);
+                }
+                "initial_call" => {
+                    info.initial_call = term_to_mfa_string(value);
+                }
+                "current_function" => {
+                    info.current_function = term_to_mfa_string(value);
+                }
+                "reductions" => {
+                    info.reductions = term_to_u64(value)?;
+                }
+                "memory" => {
+                    info.memory = term_to_u64(value)?;
+                }
+                "message_queue_len" => {
+                    info.message_queue_len = term_to_u64(value)?;
+                }
+                "total_heap_size" => {
+                    info.total_heap_size = term_to_u64(value)?;
+                }
+                k => {
+                    log::debug!("unknown process_info key: {:?}", k);
+                }
+            }
+        }
+        Ok(info)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EtsTableInfo {
+    /// Unique identifier of the table (the name for named tables).
+    pub id: String,
+    pub name: String,
+    pub owner: String,
+    pub table_type: String,
+    pub protection: String,
+    pub size: u64,
+    pub memory: u64, // in words
+}
+
+impl EtsTableInfo {
+    fn from_term(table: Term, term: Term) -> anyhow::Result<Self> {
+        let mut name = None;
+        let mut owner = None;
+        let mut table_type = None;
+        let mut protection = None;
+        let mut size = None;
+        let mut memory = None;
+        for (key, value) in term_to_key_value_list(term)? {
+            match key.as_str() {
+                "name" => {
+                    name = Some(term_to_atom(value)?.name);
+                }
+                "owner" => {
+                    owner = Some(format_term(&value));
+                }
+                "type" => {
+                    table_type = Some(term_to_atom(value)?.name);
+                }
+                "protection" => {
+                    protection = Some(term_to_atom(value)?.name);
+                }
+                "size" => {
+                    size = Some(term_to_u64(value)?);
+                }
+                "memory" => {
+                    memory = Some(term_to_u64(value)?);
+                }
+                _ => {}
+            }
+        }
+        let name = name.ok_or_else(|| anyhow::anyhow!("missing 'name' key"))?;
+        Ok(Self {
+            id: ets_table_id(&table, &name),
+            name,
+            owner: owner.ok_or_else(|| anyhow::anyhow!("missing 'owner' key"))?,
+            table_type: table_type.ok_or_else(|| anyhow::anyhow!("missing 'type' key"))?,
+            protection: protection.ok_or_else(|| anyhow::anyhow!("missing 'protection' key"))?,
+            size: size.ok_or_else(|| anyhow::anyhow!("missing 'size' key"))?,
+            memory: memory.ok_or_else(|| anyhow::anyhow!("missing 'memory' key"))?,
+        })
+    }
+}
+
+/// Returns the name of a named table, or the name followed by the reference of an unnamed table.
+fn ets_table_id(table: &Term, name: &str) -> String {
+    if matches!(table, Term::Atom(_)) {
+        name.to_owned()
+    } else {
+        format!("{name}({})", format_term(table))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EtsTableSize {
+    /// The same as [`EtsTableInfo::id`].
+    pub id: String,
+    pub size: u64,
+    pub memory: u64, // in words
+}
+
+#[derive(Debug, Clone)]
+pub struct PortInfo {
+    pub id: String,
+    pub name: String,
+    pub registered_name: Option<String>,
+    pub connected: String,
+    pub input_bytes: u64,
+    pub output_bytes: u64,
+    pub os_pid: Option<u64>,
+    pub queue_size: u64,
+
+    /// Statistics obtained via `inet:getstat/1` (only available for sockets).
+    pub socket_stats: Option<BTreeMap<String, u64>>,
+}
+
+impl PortInfo {
+    const SOCKET_DRIVERS: &'static [&'static str] = &["tcp_inet", "udp_inet", "sctp_inet"];
+
+    fn from_term(port: &Term, term: Term) -> anyhow::Result<Self> {
+        let mut info = Self {
+            id: format_term(port),
+            name: String::new(),
+            registered_name: None,
+            connected: String::new(),
+            input_bytes: 0,
+            output_bytes: 0,
+            os_pid: None,
+            queue_size: 0,
+            socket_stats: None,
+        };
+        for (key, value) in term_to_key_value_list(term)? {
+            match key.as_str() {
+                "name" => {
+                    info.name =
+                        term_to_string(value.clone()).unwrap_or_else(|_| format_term(&value));
+                }
+                "registered_name" => {
+                    info.registered_name = Some(term_to_atom(value)?.name);
+                }
+                "connected" => {
+                    info.connected = format_term(&value);
+                }
+                "input" => {
+                    info.input_bytes = term_to_u64(value)?;
+                }
+                "output" => {
+                    info.output_bytes = term_to_u64(value)?;
+                }
+                "os_pid" => {
+                    // `undefined` is returned if the port is not an OS process.
+                    info.os_pid = term_to_u64(value).ok();
+                }
+                _ => {}
+            }
+        }
+        Ok(info)
+    }
+}
@@ -0,0 +1,176 @@
+//! Converts record files into tabular formats (CSV, TSV and JSON).
+use crate::metrics::{self, Header, MetricValue, Metrics};
+use crate::record;
+use crate::{ExportArgs, ExportFormat};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::time::Duration;
+
+pub fn run(args: &ExportArgs) -> anyhow::Result<()> {
+    let (header, metrics_log) = record::load_record_file(&args.file)?;
+
+    let columns = metrics_log
+        .iter()
+        .flat_map(|metrics| metrics.items.keys())
+        .filter(|name| {
+            args.metrics.is_empty() || args.metrics.iter().any(|p| metrics::glob_match(p, name))
+        })
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let rows = metrics_log
+        .iter()
+        .filter(|metrics| !metrics.disconnected)
+        .map(|metrics| Row::new(metrics, &columns))
+        .collect::<Vec<_>>();
+    let rows = if let Some(interval) = args.interval {
+        resample(rows, Duration::from_secs(interval.get()))
+    } else {
+        rows
+    };
+
+    let stdout = std::io::stdout();
+    let mut writer = std::io::BufWriter::new(stdout.lock());
+    match args.format {
+        ExportFormat::Csv => write_delimited(&mut writer, &header, &columns, &rows, ',')?,
+        ExportFormat::Tsv => write_delimited(&mut writer, &header, &columns, &rows, '\t')?,
+        ExportFormat::Json => write_json(&mut writer, &header, &columns, &rows)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Row {
+    timestamp: Duration,
+    node: Option<String>,
+    values: Vec<Option<f64>>,
+}
+
+impl Row {
+    fn new(metrics: &Metrics, columns: &[String]) -> Self {
+        Self {
+            timestamp: metrics.timestamp,
+            node: metrics.node.clone(),
+            values: columns
+                .iter()
+                .map(|name| metrics.items.get(name).and_then(value_of))
+                .collect(),
+        }
+    }
+}
+
+fn value_of(value: &MetricValue) -> Option<f64> {
+    match value {
+        MetricValue::Gauge { value, .. } => Some(*value as f64),
+        MetricValue::Counter { value, .. } => *value,
+        MetricValue::Utilization { value, .. } => Some(*value),
+    }
+}
+
+/// Averages the values of the rows that belong to the same node and the same interval.
+fn resample(rows: Vec<Row>, interval: Duration) -> Vec<Row> {
+    let mut buckets = BTreeMap::<(u64, Option<String>), Vec<Row>>::new();
+    for row in rows {
+        let i = row.timestamp.as_secs() / interval.as_secs();
+        buckets.entry((i, row.node.clone())).or_default().push(row);
+    }
+
+    let mut resampled = buckets
+        .into_iter()
+        .map(|((i, node), rows)| {
+            let values = (0..rows[0].values.len())
+                .map(|column| {
+                    let values = rows
+                        .iter()
+                        .filter_map(|row| row.values[column])
+                        .collect::<Vec<_>>();
+                    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+                })
+                .collect();
+            Row {
+                timestamp: Duration::from_secs(i * interval.as_secs()),
+                node,
+                values,
+            }
+        })
+        .collect::<Vec<_>>();
+    resampled.sort_by_key(|row| row.timestamp);
+    resampled
+}
+
+fn format_timestamp(header: &Header, timestamp: Duration) -> String {
+    let time = header.start_time
+        + chrono::Duration::from_std(timestamp).unwrap_or_else(|_| chrono::Duration::zero());
+    time.to_rfc3339()
+}
+
+fn write_delimited<W: Write>(
+    writer: &mut W,
+    header: &Header,
+    columns: &[String],
+    rows: &[Row],
+    delimiter: char,
+) -> anyhow::Result<()> {
+    let escape = |field: &str| {
+        if delimiter == '\t' {
+            field.replace(['\t', '\n', '\r'], " ")
+        } else if field.contains([delimiter, '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    };
+    let delimiter = delimiter.to_string();
+
+    let mut fields = vec!["timestamp".to_owned()];
+    if header.is_cluster() {
+        fields.push("node".to_owned());
+    }
+    fields.extend(columns.iter().map(|name| escape(name)));
+    writeln!(writer, "{}", fields.join(&delimiter))?;
+
+    for row in rows {
+        let mut fields = vec![format_timestamp(header, row.timestamp)];
+        if header.is_cluster() {
+            fields.push(escape(row.node.as_deref().unwrap_or_default()));
+        }
+        fields.extend(
+            row.values
+                .iter()
+                .map(|v| v.map(|v| v.to_string()).unwrap_or_default()),
+        );
+        writeln!(writer, "{}", fields.join(&delimiter))?;
+    }
+    Ok(())
+}
+
+fn write_json<W: Write>(
+    writer: &mut W,
+    header: &Header,
+    columns: &[String],
+    rows: &[Row],
+) -> anyhow::Result<()> {
+    let rows = rows
+        .iter()
+        .map(|row| {
+            let mut object = serde_json::Map::new();
+            object.insert(
+                "timestamp".to_owned(),
+                format_timestamp(header, row.timestamp).into(),
+            );
+            if header.is_cluster() {
+                object.insert("node".to_owned(), row.node.clone().into());
+            }
+            for (name, value) in columns.iter().zip(row.values.iter()) {
+                object.insert(name.clone(), (*value).into());
+            }
+            serde_json::Value::Object(object)
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_writer_pretty(&mut *writer, &rows)?;
+    writeln!(writer)?;
+    Ok(())
+}
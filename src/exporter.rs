@@ -0,0 +1,340 @@
+//! Exposes the latest metrics in the OpenMetrics text format via HTTP.
+use crate::metrics::{MetricValue, Metrics};
+use anyhow::Context;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+const METRIC_NAME_PREFIX: &str = "erlang_";
+
+/// Clients that don't send a request (or read the response) within this time are disconnected.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum size of the request line and headers.
+const MAX_REQUEST_HEADER_BYTES: u64 = 8 * 1024;
+
+/// Rules to convert dotted metric names into OpenMetrics metric names and labels.
+///
+/// Each `*` in a pattern captures a label value.
+/// The last `*` can match any string (including `.`), while others cannot match `.`.
+/// Metrics that don't match any rule are exported without labels (other than `node`).
+const RULES: &[(&str, &str, &[&str])] = &[
+    (
+        "utilization.*.state.*",
+        "utilization_state_percent",
+        &["thread_type", "state"],
+    ),
+    (
+        "utilization.*.thread.*",
+        "utilization_thread_percent",
+        &["thread_type", "thread_id"],
+    ),
+    ("utilization.*", "utilization_percent", &["thread_type"]),
+    (
+        "scheduler_wall_time.*.*",
+        "scheduler_wall_time_percent",
+        &["scheduler_type", "scheduler_id"],
+    ),
+    ("memory.*_bytes", "memory_bytes", &["kind"]),
+    (
+        "statistics.run_queue.*",
+        "statistics_run_queue_per_scheduler",
+        &["scheduler"],
+    ),
+    (
+        "statistics.io.*_bytes",
+        "statistics_io_bytes",
+        &["direction"],
+    ),
+    (
+        "allocator.fragmentation.*.*",
+        "allocator_instance_fragmentation_percent",
+        &["allocator", "instance"],
+    ),
+    (
+        "allocator.fragmentation.*",
+        "allocator_fragmentation_percent",
+        &["allocator"],
+    ),
+    (
+        "allocator.blocks_bytes.*",
+        "allocator_blocks_bytes",
+        &["allocator"],
+    ),
+    (
+        "allocator.carriers_bytes.*",
+        "allocator_carriers_bytes",
+        &["allocator"],
+    ),
+    ("ets.size.*", "ets_table_size", &["table"]),
+    ("ets.memory_bytes.*", "ets_table_memory_bytes", &["table"]),
+    (
+        "distribution.input_packets.*",
+        "distribution_input_packets",
+        &["peer"],
+    ),
+    (
+        "distribution.output_packets.*",
+        "distribution_output_packets",
+        &["peer"],
+    ),
+    (
+        "distribution.input_bytes.*",
+        "distribution_input_bytes",
+        &["peer"],
+    ),
+    (
+        "distribution.output_bytes.*",
+        "distribution_output_bytes",
+        &["peer"],
+    ),
+    (
+        "distribution.queue_size_bytes.*",
+        "distribution_queue_size_bytes",
+        &["peer"],
+    ),
+    (
+        "distribution.busy_samples.*",
+        "distribution_busy_samples",
+        &["peer"],
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub struct Exporter {
+    // `None` means that the node is disconnected.
+    latest: Arc<Mutex<BTreeMap<String, Option<BTreeMap<String, MetricValue>>>>>,
+}
+
+impl Exporter {
+    pub fn start(addr: SocketAddr) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .with_context(|| format!("failed to bind to the address {addr}"))?;
+        log::info!("started OpenMetrics exporter on http://{addr}/metrics");
+
+        let exporter = Self {
+            latest: Arc::new(Mutex::new(BTreeMap::new())),
+        };
+        let this = exporter.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let this = this.clone();
+                        std::thread::spawn(move || {
+                            if let Err(e) = this.handle_connection(stream) {
+                                log::warn!("failed to handle an HTTP request: {e}");
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        log::warn!("failed to accept an HTTP connection: {e}");
+                    }
+                }
+            }
+        });
+        Ok(exporter)
+    }
+
+    pub fn update(&self, node: &str, metrics: &Metrics) {
+        let items = (!metrics.disconnected).then(|| metrics.items.clone());
+        self.latest
+            .lock()
+            .expect("unreachable")
+            .insert(node.to_owned(), items);
+    }
+
+    fn handle_connection(&self, stream: TcpStream) -> anyhow::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?.take(MAX_REQUEST_HEADER_BYTES));
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+                break;
+            }
+        }
+        anyhow::ensure!(
+            reader.get_ref().limit() > 0,
+            "the request header exceeds {MAX_REQUEST_HEADER_BYTES} bytes"
+        );
+
+        let mut tokens = request_line.split_whitespace();
+        let method = tokens.next().unwrap_or_default();
+        let path = tokens.next().unwrap_or_default();
+        log::debug!("HTTP request: {method} {path}");
+
+        let (status, content_type, body) = match (method, path) {
+            ("GET", "/metrics") => ("200 OK", CONTENT_TYPE, self.render()),
+            ("GET", _) => ("404 Not Found", "text/plain", "Not Found\n".to_owned()),
+            _ => (
+                "405 Method Not Allowed",
+                "text/plain",
+                "Method Not Allowed\n".to_owned(),
+            ),
+        };
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )?;
+        stream.flush()?;
+        Ok(())
+    }
+
+    fn render(&self) -> String {
+        let mut families = BTreeMap::<String, MetricFamily>::new();
+        for (node, items) in self.latest.lock().expect("unreachable").iter() {
+            let node_label = vec![("node".to_owned(), node.clone())];
+            families
+                .entry("erldash_node_up".to_owned())
+                .or_insert_with(|| MetricFamily::new("gauge"))
+                .samples
+                .push(Sample {
+                    name: "erldash_node_up".to_owned(),
+                    labels: node_label.clone(),
+                    value: if items.is_some() { "1" } else { "0" }.to_owned(),
+                });
+
+            for (name, value) in items.iter().flatten() {
+                let (family_name, mut labels) = to_family_name_and_labels(name);
+                labels.splice(0..0, node_label.iter().cloned());
+                let (metric_type, sample_name, value) = match value {
+                    MetricValue::Gauge { value, .. } => {
+                        ("gauge", family_name.clone(), value.to_string())
+                    }
+                    MetricValue::Counter { raw_value, .. } => (
+                        "counter",
+                        format!("{family_name}_total"),
+                        raw_value.to_string(),
+                    ),
+                    MetricValue::Utilization { value, .. } => {
+                        ("gauge", family_name.clone(), value.to_string())
+                    }
+                };
+                families
+                    .entry(family_name)
+                    .or_insert_with(|| MetricFamily::new(metric_type))
+                    .samples
+                    .push(Sample {
+                        name: sample_name,
+                        labels,
+                        value,
+                    });
+            }
+        }
+
+        let mut text = String::new();
+        for (name, family) in families {
+            text.push_str(&format!("# TYPE {name} {}\n", family.metric_type));
+            for sample in family.samples {
+                let labels = sample
+                    .labels
+                    .iter()
+                    .map(|(k, v)| format!("{k}=\"{}\"", escape_label_value(v)))
+                    .collect::<Vec<_>>();
+                text.push_str(&format!(
+                    "{}{{{}}} {}\n",
+                    sample.name,
+                    labels.join(","),
+                    sample.value
+                ));
+            }
+        }
+        text.push_str("# EOF\n");
+        text
+    }
+}
+
+#[derive(Debug)]
+struct MetricFamily {
+    metric_type: &'static str,
+    samples: Vec<Sample>,
+}
+
+impl MetricFamily {
+    fn new(metric_type: &'static str) -> Self {
+        Self {
+            metric_type,
+            samples: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Sample {
+    name: String,
+    labels: Vec<(String, String)>,
+    value: String,
+}
+
+fn to_family_name_and_labels(name: &str) -> (String, Vec<(String, String)>) {
+    for (pattern, family_name, label_names) in RULES {
+        let mut captures = Vec::new();
+        if match_pattern(pattern, name, &mut captures) {
+            let labels = label_names
+                .iter()
+                .zip(captures)
+                .map(|(k, v)| (k.to_string(), v.to_owned()))
+                .collect();
+            return (format!("{METRIC_NAME_PREFIX}{family_name}"), labels);
+        }
+    }
+    (
+        format!("{METRIC_NAME_PREFIX}{}", sanitize(name)),
+        Vec::new(),
+    )
+}
+
+fn match_pattern<'a>(pattern: &str, name: &'a str, captures: &mut Vec<&'a str>) -> bool {
+    let Some((prefix, rest_pattern)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(name) = name.strip_prefix(prefix) else {
+        return false;
+    };
+
+    if !rest_pattern.contains('*') {
+        let Some(captured) = name.strip_suffix(rest_pattern) else {
+            return false;
+        };
+        if captured.is_empty() {
+            return false;
+        }
+        captures.push(captured);
+        return true;
+    }
+
+    for (i, c) in name.char_indices() {
+        if i > 0 {
+            captures.push(&name[..i]);
+            if match_pattern(rest_pattern, &name[i..], captures) {
+                return true;
+            }
+            captures.pop();
+        }
+        if c == '.' {
+            return false;
+        }
+    }
+    false
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,7 +1,14 @@
 //! A simple, terminal-based Erlang dashboard.
 use std::path::PathBuf;
+pub mod alert;
+pub mod check;
+pub mod collector;
+pub mod config;
 pub mod erlang;
+pub mod export;
+pub mod exporter;
 pub mod metrics;
+pub mod record;
 pub mod ui;
 
 #[derive(Debug, Clone, clap::Subcommand)]
@@ -11,16 +18,82 @@ pub enum Command {
 
     /// Replay a previously recorded dashboard session.
     Replay(ReplayArgs),
+
+    /// Serve the collected metrics in the OpenMetrics text format without the dashboard UI.
+    Serve(ServeArgs),
+
+    /// Record the collected metrics to a file without the dashboard UI.
+    ///
+    /// The recording stops when SIGINT or SIGTERM is received, or when one of the given limits is reached.
+    Record(RecordArgs),
+
+    /// Export a record file to CSV, TSV or JSON (one row per sample and one column per metric).
+    Export(ExportArgs),
+
+    /// Check assertions on the metrics of the target nodes (or a record file).
+    ///
+    /// The exit code is non-zero if any of the assertions fails.
+    Check(CheckArgs),
+}
+
+impl Command {
+    /// Returns `true` if the command runs without the dashboard UI.
+    pub fn is_headless(&self) -> bool {
+        matches!(self, Self::Serve(_) | Self::Record(_))
+    }
+
+    pub fn chart_window(&self) -> ChartWindow {
+        match self {
+            Self::Run(args) => args.window,
+            Self::Replay(args) => args.window,
+            _ => ChartWindow::OneMinute,
+        }
+    }
 }
 
 #[derive(Debug, Clone, clap::Args)]
 pub struct RunArgs {
-    /// Target Erlang node name.
-    pub erlang_node: erl_dist::node::NodeName,
+    /// Target Erlang node names.
+    ///
+    /// If multiple nodes are specified, `erldash` runs in cluster mode.
+    #[clap(required = true)]
+    pub erlang_nodes: Vec<erl_dist::node::NodeName>,
 
-    /// Erlang metrics polling interval (in seconds).
-    #[clap(long, short = 'i', default_value = "1")]
-    pub polling_interval: std::num::NonZeroUsize,
+    #[clap(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// If specified, the collected metrics will be recorded to the given file and can be replayed later.
+    #[clap(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Format of the record file.
+    ///
+    /// `binary` is more compact and suitable for long captures.
+    /// Both formats can be replayed and exported.
+    #[clap(long, value_enum, default_value_t = RecordFormat::Json)]
+    pub record_format: RecordFormat,
+
+    /// Time window of the charts and the averages.
+    #[clap(long, value_enum, default_value_t = ChartWindow::OneMinute)]
+    pub window: ChartWindow,
+
+    /// If specified, the latest metrics are exposed in the OpenMetrics text format at `http://ADDR/metrics`.
+    #[clap(long, value_name = "ADDR")]
+    pub listen: Option<std::net::SocketAddr>,
+}
+
+/// Options on how to connect to the target nodes and what to collect from them.
+#[derive(Debug, Clone, clap::Args)]
+pub struct ConnectionArgs {
+    /// If specified, the nodes connected to the first target node (i.e., `erlang:nodes()`) are also monitored in cluster mode.
+    #[clap(long)]
+    pub discover: bool,
+
+    /// Erlang metrics polling interval (e.g., `1`, `0.5s` or `250ms`).
+    ///
+    /// A number without a unit is interpreted as seconds.
+    #[clap(long, short = 'i', default_value = "1", value_parser = parse_duration_arg)]
+    pub polling_interval: std::time::Duration,
 
     /// Erlang cookie.
     ///
@@ -28,18 +101,67 @@ pub struct RunArgs {
     #[clap(long, short = 'c')]
     pub cookie: Option<String>,
 
-    /// If specified, the collected metrics will be recorded to the given file and can be replayed later.
-    #[clap(long, value_name = "FILE")]
-    pub record: Option<PathBuf>,
-
     /// Port number on which the target node listens.
     ///
     /// If specified, `erldash` will connect directly to the node without using EPMD.
+    /// This option cannot be used with multiple target nodes.
     #[clap(long, short)]
     pub port: Option<u16>,
+
+    /// Path to a JSON config file (e.g., alert rules).
+    #[clap(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// If specified, `erldash` never changes the system flags of the target nodes.
+    ///
+    /// Microstate accounting is not toggled in this mode, so scheduler utilization is derived
+    /// from `erlang:statistics(scheduler_wall_time_all)` instead and the per-state and non-scheduler
+    /// thread utilization metrics are unavailable.
+    #[clap(long)]
+    pub read_only: bool,
+
+    /// In read-only mode, allows `erldash` to enable the `scheduler_wall_time` system flag if it is disabled.
+    ///
+    /// The flag is enabled by a process that `erldash` spawns on the target node and kills on exit.
+    /// The flag is reference counted per process by the runtime, so this doesn't affect other tools.
+    #[clap(long, requires = "read_only")]
+    pub enable_scheduler_wall_time: bool,
+
+    /// Timeout of each RPC to the target nodes (e.g., `5s` or `500ms`).
+    ///
+    /// Metrics whose RPCs failed or timed out are shown as `n/a` for that sample.
+    #[clap(long, default_value = "5s", value_parser = parse_duration_arg)]
+    pub rpc_timeout: std::time::Duration,
+
+    /// Metrics to be collected, specified by glob patterns (e.g., `memory.*`) or group names (e.g., `memory` or `msacc`).
+    ///
+    /// This option can be specified multiple times.
+    /// If omitted, all metrics are collected.
+    /// Groups none of whose metrics are selected are not polled, and unselected metrics are neither shown nor recorded.
+    #[clap(long, value_name = "PATTERN")]
+    pub metrics: Vec<String>,
+
+    /// Metrics not to be collected (the same syntax as `--metrics`).
+    ///
+    /// This option can be specified multiple times.
+    #[clap(long, value_name = "PATTERN")]
+    pub exclude_metrics: Vec<String>,
+}
+
+fn parse_duration_arg(s: &str) -> Result<std::time::Duration, String> {
+    let with_unit = if s.ends_with(|c: char| c.is_ascii_digit()) {
+        format!("{s}s")
+    } else {
+        s.to_owned()
+    };
+    alert::parse_duration(&with_unit)
+        .filter(|d| !d.is_zero())
+        .ok_or_else(|| {
+            format!("expected a positive duration such as `1`, `0.5s` or `250ms`, but got {s:?}")
+        })
 }
 
-impl RunArgs {
+impl ConnectionArgs {
     pub fn find_cookie(&self) -> anyhow::Result<String> {
         if let Some(cookie) = &self.cookie {
             Ok(cookie.clone())
@@ -47,10 +169,207 @@ impl RunArgs {
             erlang::find_cookie()
         }
     }
+
+    pub fn load_config(&self) -> anyhow::Result<config::Config> {
+        if let Some(path) = &self.config {
+            config::Config::load(path)
+        } else {
+            Ok(config::Config::default())
+        }
+    }
 }
 
 #[derive(Debug, Clone, clap::Args)]
 pub struct ReplayArgs {
     /// Path to a file containing recorded metrics.
     pub file: PathBuf,
+
+    /// Time window of the charts and the averages.
+    #[clap(long, value_enum, default_value_t = ChartWindow::OneMinute)]
+    pub window: ChartWindow,
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct ServeArgs {
+    /// Target Erlang node names.
+    #[clap(required = true)]
+    pub erlang_nodes: Vec<erl_dist::node::NodeName>,
+
+    #[clap(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Address on which the latest metrics are exposed at `http://ADDR/metrics`.
+    #[clap(long, value_name = "ADDR")]
+    pub listen: std::net::SocketAddr,
+}
+
+impl ServeArgs {
+    /// Returns the equivalent arguments of `run` command that expose the metrics at [`ServeArgs::listen`].
+    pub fn to_run_args(&self) -> RunArgs {
+        RunArgs {
+            erlang_nodes: self.erlang_nodes.clone(),
+            connection: self.connection.clone(),
+            record: None,
+            record_format: RecordFormat::Json,
+            window: ChartWindow::OneMinute,
+            listen: Some(self.listen),
+        }
+    }
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct RecordArgs {
+    /// Target Erlang node names.
+    #[clap(required = true)]
+    pub erlang_nodes: Vec<erl_dist::node::NodeName>,
+
+    #[clap(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Path to the file to which the collected metrics are recorded.
+    #[clap(long, short, value_name = "FILE")]
+    pub output: PathBuf,
+
+    /// Format of the record file.
+    #[clap(long, value_enum, default_value_t = RecordFormat::Json)]
+    pub record_format: RecordFormat,
+
+    /// If specified, the recording stops after the given number of seconds.
+    #[clap(long, value_name = "SECONDS")]
+    pub duration: Option<u64>,
+
+    /// If specified, the recording stops once the size of the recorded data reaches the given number of bytes.
+    #[clap(long, value_name = "BYTES")]
+    pub max_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct ExportArgs {
+    /// Path to a file containing recorded metrics.
+    pub file: PathBuf,
+
+    /// Output format.
+    #[clap(long, short, value_enum, default_value_t = ExportFormat::Csv)]
+    pub format: ExportFormat,
+
+    /// Glob pattern of metric names to be exported (e.g., `memory.*`).
+    ///
+    /// This option can be specified multiple times.
+    /// If omitted, all metrics are exported.
+    #[clap(long = "metric", short, value_name = "GLOB")]
+    pub metrics: Vec<String>,
+
+    /// If specified, the samples are resampled to the given interval (in seconds) by averaging.
+    #[clap(long, value_name = "SECONDS")]
+    pub interval: Option<std::num::NonZeroU64>,
+}
+
+impl RecordArgs {
+    /// Returns the equivalent arguments of `run` command that record the metrics to [`RecordArgs::output`].
+    pub fn to_run_args(&self) -> RunArgs {
+        RunArgs {
+            erlang_nodes: self.erlang_nodes.clone(),
+            connection: self.connection.clone(),
+            record: Some(self.output.clone()),
+            record_format: self.record_format,
+            window: ChartWindow::OneMinute,
+            listen: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RecordFormat {
+    Json,
+    Binary,
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct CheckArgs {
+    /// Target Erlang node names (not required if `--replay` is specified).
+    #[clap(required_unless_present = "replay")]
+    pub erlang_nodes: Vec<erl_dist::node::NodeName>,
+
+    #[clap(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Assertion to be checked (e.g., `max utilization.scheduler < 90%`).
+    ///
+    /// The syntax is `AGGREGATION METRIC OP THRESHOLD`, where `AGGREGATION` is one of `min`, `max`, `avg` and `last`.
+    /// This option can be specified multiple times.
+    #[clap(long = "assert", short, value_name = "ASSERTION", required = true)]
+    pub assertions: Vec<check::Assertion>,
+
+    /// Number of samples collected from each target node.
+    ///
+    /// An extra sample is polled first as the baseline of the counter rates.
+    #[clap(long, short = 'n', default_value = "10")]
+    pub samples: std::num::NonZeroUsize,
+
+    /// If specified, the assertions are checked against the given record file instead of the target nodes.
+    #[clap(long, value_name = "FILE", conflicts_with = "erlang_nodes")]
+    pub replay: Option<PathBuf>,
+}
+
+impl CheckArgs {
+    /// Returns the equivalent arguments of `run` command that collect the samples to be checked.
+    pub fn to_run_args(&self) -> RunArgs {
+        RunArgs {
+            erlang_nodes: self.erlang_nodes.clone(),
+            connection: self.connection.clone(),
+            record: None,
+            record_format: RecordFormat::Json,
+            window: ChartWindow::OneMinute,
+            listen: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ChartWindow {
+    #[value(name = "1m")]
+    OneMinute,
+    #[value(name = "5m")]
+    FiveMinutes,
+    #[value(name = "15m")]
+    FifteenMinutes,
+    #[value(name = "1h")]
+    OneHour,
+}
+
+impl ChartWindow {
+    pub fn next(self) -> Self {
+        match self {
+            Self::OneMinute => Self::FiveMinutes,
+            Self::FiveMinutes => Self::FifteenMinutes,
+            Self::FifteenMinutes => Self::OneHour,
+            Self::OneHour => Self::OneMinute,
+        }
+    }
+
+    pub fn duration(self) -> std::time::Duration {
+        let minutes = match self {
+            Self::OneMinute => 1,
+            Self::FiveMinutes => 5,
+            Self::FifteenMinutes => 15,
+            Self::OneHour => 60,
+        };
+        std::time::Duration::from_secs(minutes * 60)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OneMinute => "1m",
+            Self::FiveMinutes => "5m",
+            Self::FifteenMinutes => "15m",
+            Self::OneHour => "1h",
+        }
+    }
 }
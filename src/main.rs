@@ -23,9 +23,29 @@ fn main() -> anyhow::Result<()> {
     let args = Args::parse();
     setup_logger(&args)?;
 
+    match args.command {
+        erldash::Command::Export(args) => return erldash::export::run(&args),
+        erldash::Command::Check(args) => {
+            if !erldash::check::run(args)? {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let command = args.command.clone();
     let poller = metrics::MetricsPoller::start_thread(args.command)?;
-    let app = ui::App::new(poller)?;
-    app.run()?;
+    if command.is_headless() {
+        let record_args = match &command {
+            erldash::Command::Record(args) => Some(args),
+            _ => None,
+        };
+        poller.run_headless(record_args)?;
+    } else {
+        let app = ui::App::new(poller, command.chart_window())?;
+        app.run()?;
+    }
     Ok(())
 }
 
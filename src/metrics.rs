@@ -1,32 +1,112 @@
-use crate::erlang::{MSAccThread, RpcClient, SystemVersion};
-use crate::{Command, ReplayArgs, RunArgs};
+use crate::alert::{Alert, AlertEvaluator};
+use crate::collector::{self, Collector, CollectorFactory};
+use crate::config::Config;
+use crate::erlang::{
+    EtsTableInfo, PortInfo, ProcessDetail, ProcessInfo, RpcClient, RpcTimeoutError, SystemVersion,
+};
+use crate::exporter::Exporter;
+use crate::record::{RecordReader, Recorder};
+use crate::{Command, RecordArgs, ReplayArgs, RunArgs};
 use anyhow::Context;
+use erl_dist::node::NodeName;
+use erl_dist::term::Pid;
 use serde::{Deserialize, Serialize};
-use smol::fs::File;
-use smol::io::AsyncWriteExt;
-use std::collections::BTreeMap;
-use std::io::BufRead;
-use std::sync::mpsc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::{Duration, Instant};
 
 type MetricsReceiver = mpsc::Receiver<Metrics>;
 type MetricsSender = mpsc::Sender<Metrics>;
 
+const MIN_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+const HEADLESS_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metrics {
     pub timestamp: Duration,
     pub items: BTreeMap<String, MetricValue>,
+
+    /// The name of the node from which the metrics were collected (only set in cluster mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+
+    /// If `true`, this is not an actual sample but a marker indicating that the connection to the node was lost.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disconnected: bool,
+
+    /// Only collected while subscribed (see [`Subscription`]) and never recorded.
+    #[serde(skip)]
+    pub processes: Vec<ProcessMetrics>,
+
+    /// Only collected while subscribed (see [`Subscription`]) and never recorded.
+    #[serde(skip)]
+    pub process_detail: Option<ProcessDetail>,
+
+    /// Only collected while subscribed (see [`Subscription`]) and never recorded.
+    #[serde(skip)]
+    pub ports: Vec<PortMetrics>,
+
+    /// Only collected while subscribed (see [`Subscription`]) and never recorded.
+    #[serde(skip)]
+    pub ets_tables: Vec<EtsTableMetrics>,
+
+    /// Alerts firing at the time of this sample (never recorded).
+    #[serde(skip)]
+    pub alerts: Vec<Alert>,
+
+    /// Set if polling this sample took longer than the polling interval (never recorded).
+    #[serde(skip)]
+    pub slow_poll: Option<Duration>,
+
+    /// Collectors that failed in this sample (never recorded).
+    #[serde(skip)]
+    pub errors: Vec<CollectorError>,
+
+    /// Root metrics that were present in the previous sample but are missing due to `errors` (never recorded).
+    #[serde(skip)]
+    pub missing: Vec<String>,
+}
+
+/// A failure of a group of metrics (e.g., `memory`) in a sample.
+#[derive(Debug, Clone)]
+pub struct CollectorError {
+    pub collector: String,
+    pub message: String,
 }
 
 impl Metrics {
     fn new(start: Instant) -> Self {
+        Self::with_timestamp(start.elapsed())
+    }
+
+    pub fn with_timestamp(timestamp: Duration) -> Self {
         Self {
-            timestamp: start.elapsed(),
+            timestamp,
             items: BTreeMap::new(),
+            node: None,
+            disconnected: false,
+            processes: Vec::new(),
+            process_detail: None,
+            ports: Vec::new(),
+            ets_tables: Vec::new(),
+            alerts: Vec::new(),
+            slow_poll: None,
+            errors: Vec::new(),
+            missing: Vec::new(),
         }
     }
 
-    fn insert(&mut self, name: &str, value: MetricValue) {
+    fn disconnected_marker(start: Instant, node: Option<String>) -> Self {
+        Self {
+            node,
+            disconnected: true,
+            ..Self::new(start)
+        }
+    }
+
+    pub fn insert(&mut self, name: &str, value: MetricValue) {
         self.items.insert(name.to_owned(), value);
     }
 
@@ -51,7 +131,10 @@ impl Metrics {
     }
 
     fn calc_delta(&mut self, prev: &Self) {
-        let duration = self.timestamp - prev.timestamp;
+        let duration = self.timestamp.saturating_sub(prev.timestamp);
+        if duration.is_zero() {
+            return;
+        }
         for (name, value) in &mut self.items {
             if let MetricValue::Counter {
                 raw_value, value, ..
@@ -67,9 +150,136 @@ impl Metrics {
                 }
             }
         }
+
+        let prev_reductions = prev
+            .processes
+            .iter()
+            .map(|p| (p.info.pid.to_string(), p.info.reductions))
+            .collect::<HashMap<_, _>>();
+        for process in &mut self.processes {
+            if let Some(prev) = prev_reductions.get(&process.info.pid.to_string()) {
+                if let Some(delta) = process.info.reductions.checked_sub(*prev) {
+                    process.reductions = Some(delta as f64 / duration.as_secs_f64());
+                }
+            }
+        }
+
+        let prev_ports = prev
+            .ports
+            .iter()
+            .map(|p| (p.info.id.as_str(), p))
+            .collect::<HashMap<_, _>>();
+        for port in &mut self.ports {
+            if let Some(prev) = prev_ports.get(port.info.id.as_str()) {
+                let secs = duration.as_secs_f64();
+                if let Some(delta) = port.info.input_bytes.checked_sub(prev.info.input_bytes) {
+                    port.input_bytes = Some(delta as f64 / secs);
+                }
+                if let Some(delta) = port.info.output_bytes.checked_sub(prev.info.output_bytes) {
+                    port.output_bytes = Some(delta as f64 / secs);
+                }
+            }
+        }
+
+        let prev_ets_tables = prev
+            .ets_tables
+            .iter()
+            .map(|t| (t.info.id.as_str(), t))
+            .collect::<HashMap<_, _>>();
+        for table in &mut self.ets_tables {
+            if let Some(prev) = prev_ets_tables.get(table.info.id.as_str()) {
+                let secs = duration.as_secs_f64();
+                table.size_growth = Some((table.info.size as f64 - prev.info.size as f64) / secs);
+                table.memory_growth =
+                    Some((table.memory_bytes as f64 - prev.memory_bytes as f64) / secs);
+            }
+        }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct PortMetrics {
+    pub info: PortInfo,
+    pub input_bytes: Option<f64>,  // delta per second
+    pub output_bytes: Option<f64>, // delta per second
+}
+
+impl PortMetrics {
+    fn new(info: PortInfo) -> Self {
+        Self {
+            info,
+            input_bytes: None,
+            output_bytes: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.info
+            .registered_name
+            .as_deref()
+            .unwrap_or(self.info.name.as_str())
+    }
+
+    pub fn socket_stat(&self, key: &str) -> Option<u64> {
+        self.info
+            .socket_stats
+            .as_ref()
+            .and_then(|stats| stats.get(key).copied())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EtsTableMetrics {
+    pub info: EtsTableInfo,
+    pub memory_bytes: u64,
+    pub size_growth: Option<f64>,   // delta per second
+    pub memory_growth: Option<f64>, // delta per second
+}
+
+impl EtsTableMetrics {
+    fn new(info: EtsTableInfo, wordsize: u64) -> Self {
+        Self {
+            memory_bytes: info.memory * wordsize,
+            info,
+            size_growth: None,
+            memory_growth: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessMetrics {
+    pub info: ProcessInfo,
+    pub reductions: Option<f64>, // delta per second
+    pub heap_bytes: u64,
+}
+
+impl ProcessMetrics {
+    fn new(info: ProcessInfo, wordsize: u64) -> Self {
+        Self {
+            heap_bytes: info.total_heap_size * wordsize,
+            info,
+            reductions: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.info
+            .registered_name
+            .as_deref()
+            .unwrap_or(self.info.initial_call.as_str())
+    }
+}
+
+/// Optional data that the UI asks the poller to collect in addition to the regular metrics.
+#[derive(Debug, Default, Clone)]
+pub struct Subscription {
+    pub processes: bool,
+    pub process_detail: Option<Pid>,
+    pub ports: bool,
+    pub ets_tables: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MetricValue {
     Gauge {
@@ -95,28 +305,28 @@ impl MetricValue {
         }
     }
 
-    fn utilization_with_parent(value: f64, parent: &str) -> Self {
+    pub fn utilization_with_parent(value: f64, parent: &str) -> Self {
         Self::Utilization {
             value,
             parent: Some(parent.to_owned()),
         }
     }
 
-    fn gauge(value: u64) -> Self {
+    pub fn gauge(value: u64) -> Self {
         Self::Gauge {
             value,
             parent: None,
         }
     }
 
-    fn gauge_with_parent(value: u64, parent: &str) -> Self {
+    pub fn gauge_with_parent(value: u64, parent: &str) -> Self {
         Self::Gauge {
             value,
             parent: Some(parent.to_owned()),
         }
     }
 
-    fn counter(raw_value: u64) -> Self {
+    pub fn counter(raw_value: u64) -> Self {
         Self::Counter {
             raw_value,
             value: None,
@@ -124,7 +334,7 @@ impl MetricValue {
         }
     }
 
-    fn counter_with_parent(raw_value: u64, parent: &str) -> Self {
+    pub fn counter_with_parent(raw_value: u64, parent: &str) -> Self {
         Self::Counter {
             raw_value,
             value: None,
@@ -215,6 +425,220 @@ impl std::ops::SubAssign for MetricValue {
     }
 }
 
+/// Average of metric values (as shown in the "Avg" columns of the dashboard).
+#[derive(Debug, Clone)]
+pub struct AvgValue {
+    sum: MetricValue,
+    cnt: usize,
+}
+
+impl AvgValue {
+    pub fn new(value: MetricValue) -> Self {
+        Self { sum: value, cnt: 1 }
+    }
+
+    pub fn add(&mut self, v: MetricValue) {
+        self.sum += v;
+        self.cnt += 1;
+    }
+
+    pub fn sub(&mut self, v: MetricValue) {
+        self.sum -= v;
+        self.cnt -= 1;
+    }
+
+    pub fn get(&self) -> MetricValue {
+        match self.sum {
+            MetricValue::Gauge { value, .. } => {
+                let value = (value as f64 / self.cnt as f64).round() as u64;
+                MetricValue::Gauge {
+                    value,
+                    parent: None,
+                }
+            }
+            MetricValue::Counter {
+                value: Some(value), ..
+            } => {
+                let value = value / self.cnt as f64;
+                MetricValue::Counter {
+                    raw_value: 0,
+                    value: Some(value),
+                    parent: None,
+                }
+            }
+            MetricValue::Counter { .. } => MetricValue::Counter {
+                raw_value: 0,
+                value: None,
+                parent: None,
+            },
+            MetricValue::Utilization { value, .. } => {
+                let value = value / self.cnt as f64;
+                MetricValue::Utilization {
+                    value,
+                    parent: None,
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if `name` matches the glob `pattern`.
+///
+/// `*` matches any sequence of characters (including `.`) and `?` matches any single character.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    let mut last_star = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            last_star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = last_star {
+            p = star_p + 1;
+            n = star_n + 1;
+            last_star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Selection of the metrics via `--metrics` and `--exclude-metrics` options.
+///
+/// A pattern selects the metrics matching it as a glob and their descendants (e.g., `memory` selects `memory.ets_bytes`).
+/// A pattern equal to the name of a collector (e.g., `msacc`) is expanded to the metrics inserted by the collector.
+#[derive(Debug, Clone, Default)]
+pub struct MetricFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+/// Metrics inserted by the poller itself rather than by collectors.
+const POLL_METRICS: &str = "poll.*";
+
+impl MetricFilter {
+    /// Returns an error if a pattern matches no collector or metric, or no collector is selected.
+    pub fn new(args: &RunArgs, collectors: &[Box<dyn Collector>]) -> anyhow::Result<Self> {
+        // Collectors that don't declare their metrics may insert any metric.
+        let has_undeclared_metrics = collectors.iter().any(|c| c.metrics().is_empty());
+        for pattern in args
+            .connection
+            .metrics
+            .iter()
+            .chain(&args.connection.exclude_metrics)
+        {
+            let known = has_undeclared_metrics
+                || collectors.iter().any(|c| c.name() == pattern)
+                || collectors
+                    .iter()
+                    .flat_map(|c| c.metrics())
+                    .chain(std::iter::once(POLL_METRICS.to_owned()))
+                    .any(|metric| {
+                        globs_overlap(pattern, &metric)
+                            || globs_overlap(&format!("{pattern}.*"), &metric)
+                    });
+            anyhow::ensure!(
+                known,
+                "the pattern {pattern:?} matches no metric group or metric (groups are {})",
+                collectors
+                    .iter()
+                    .map(|c| c.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        let expand = |patterns: &[String]| {
+            let mut expanded = Vec::new();
+            for pattern in patterns {
+                expanded.push(pattern.clone());
+                for collector in collectors.iter().filter(|c| c.name() == pattern) {
+                    expanded.extend(collector.metrics());
+                }
+            }
+            expanded
+        };
+        let filter = Self {
+            include: expand(&args.connection.metrics),
+            exclude: expand(&args.connection.exclude_metrics),
+        };
+        anyhow::ensure!(
+            collectors
+                .iter()
+                .any(|c| filter.is_collector_selected(c.as_ref())),
+            "no metrics are selected by `--metrics` and `--exclude-metrics` options"
+        );
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Returns `true` if the given metric (or glob pattern of metrics) is selected.
+    pub fn is_selected(&self, name: &str) -> bool {
+        let matches = |pattern: &String| {
+            glob_match(pattern, name)
+                || name
+                    .strip_prefix(pattern.as_str())
+                    .map_or(false, |rest| rest.starts_with('.'))
+        };
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+
+    /// Returns `true` if the given collector may insert selected metrics.
+    pub fn is_collector_selected(&self, collector: &dyn Collector) -> bool {
+        let name = collector.name();
+        if self.exclude.iter().any(|x| x == name) {
+            return false;
+        }
+        if self.include.iter().any(|x| x == name) {
+            return true;
+        }
+        let patterns = collector.metrics();
+        if patterns.is_empty() {
+            return self.include.is_empty();
+        }
+
+        let included = self.include.is_empty()
+            || patterns.iter().any(|metric| {
+                self.include.iter().any(|pattern| {
+                    globs_overlap(pattern, metric) || globs_overlap(&format!("{pattern}.*"), metric)
+                })
+            });
+        // A collector is excluded only if all of its metrics are excluded.
+        let excluded = patterns.iter().all(|metric| {
+            self.exclude.iter().any(|pattern| {
+                glob_match(pattern, metric) || metric.starts_with(&format!("{pattern}."))
+            })
+        });
+        included && !excluded
+    }
+}
+
+/// Returns `true` if there is a name matching both of the given glob patterns.
+fn globs_overlap(a: &str, b: &str) -> bool {
+    fn overlap(a: &[char], b: &[char]) -> bool {
+        match (a.first(), b.first()) {
+            (None, None) => true,
+            (Some('*'), _) => overlap(&a[1..], b) || (!b.is_empty() && overlap(a, &b[1..])),
+            (_, Some('*')) => overlap(a, &b[1..]) || (!a.is_empty() && overlap(&a[1..], b)),
+            (Some(x), Some(y)) => (*x == '?' || *y == '?' || x == y) && overlap(&a[1..], &b[1..]),
+            _ => false,
+        }
+    }
+    overlap(
+        &a.chars().collect::<Vec<_>>(),
+        &b.chars().collect::<Vec<_>>(),
+    )
+}
+
 pub fn format_u64(mut n: u64, suffix: &str) -> String {
     let mut s = Vec::new();
     for i in 0.. {
@@ -242,16 +666,93 @@ pub enum MetricsPoller {
 
 impl MetricsPoller {
     pub fn start_thread(command: Command) -> anyhow::Result<Self> {
+        Self::start_thread_with_collectors(command, &[])
+    }
+
+    /// Same as [`MetricsPoller::start_thread()`] but also runs the collectors created by `collectors`
+    /// for each target node (in addition to the built-in ones).
+    pub fn start_thread_with_collectors(
+        command: Command,
+        collectors: &[CollectorFactory],
+    ) -> anyhow::Result<Self> {
         match command {
-            Command::Run(args) => RealtimeMetricsPoller::start_thread(args).map(Self::Realtime),
+            Command::Run(args) => {
+                RealtimeMetricsPoller::start_thread(args, collectors).map(Self::Realtime)
+            }
             Command::Replay(args) => ReplayMetricsPoller::new(args).map(Self::Replay),
+            Command::Serve(args) => {
+                RealtimeMetricsPoller::start_thread(args.to_run_args(), collectors)
+                    .map(Self::Realtime)
+            }
+            Command::Export(_) | Command::Check(_) => {
+                anyhow::bail!(
+                    "this command doesn't poll metrics via `MetricsPoller::start_thread()`"
+                )
+            }
+            Command::Record(args) => {
+                RealtimeMetricsPoller::start_thread(args.to_run_args(), collectors)
+                    .map(Self::Realtime)
+            }
         }
     }
 
+    /// Keeps receiving metrics without displaying them.
+    ///
+    /// This method returns when SIGINT or SIGTERM is received, all polling threads terminate,
+    /// or one of the limits in `record_args` is reached.
+    /// The microstate accounting flags of the target nodes are restored when `self` is dropped.
+    pub fn run_headless(self, record_args: Option<&RecordArgs>) -> anyhow::Result<()> {
+        let Self::Realtime(poller) = &self else {
+            anyhow::bail!("`run_headless()` is only available in realtime mode");
+        };
+
+        let terminated = Arc::new(AtomicBool::new(false));
+        for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+            signal_hook::flag::register(signal, terminated.clone())?;
+        }
+
+        let start = Instant::now();
+        while !terminated.load(Ordering::SeqCst) {
+            if let Err(mpsc::RecvTimeoutError::Disconnected) =
+                poller.rx.recv_timeout(HEADLESS_POLL_TIMEOUT)
+            {
+                log::debug!("all polling threads have terminated");
+                return Ok(());
+            }
+
+            let Some(args) = record_args else {
+                continue;
+            };
+            if let Some(duration) = args.duration {
+                if start.elapsed() >= Duration::from_secs(duration) {
+                    log::info!("reached the recording duration limit");
+                    return Ok(());
+                }
+            }
+            if let (Some(max_size), Some(recorder)) = (args.max_size, &poller.recorder) {
+                if smol::block_on(recorder.size()) >= max_size {
+                    log::info!("reached the recording file size limit");
+                    return Ok(());
+                }
+            }
+        }
+        log::info!("received a termination signal");
+        Ok(())
+    }
+
     pub fn is_replay(&self) -> bool {
         matches!(self, Self::Replay(_))
     }
 
+    /// `node_index` is the index of the target node in [`Header::nodes()`].
+    pub fn subscribe(&self, node_index: usize, subscription: Subscription) {
+        if let Self::Realtime(poller) = self {
+            if let Some(node) = poller.nodes.get(node_index) {
+                *node.subscription.lock().expect("unreachable") = subscription;
+            }
+        }
+    }
+
     pub fn header(&self) -> &Header {
         match self {
             Self::Realtime(poller) => &poller.header,
@@ -271,11 +772,7 @@ impl MetricsPoller {
     pub fn replay_last_time(&self) -> Duration {
         match self {
             Self::Realtime(_) => Duration::from_secs(0),
-            Self::Replay(poller) => poller
-                .metrics_log
-                .last()
-                .map(|m| m.timestamp)
-                .unwrap_or_default(),
+            Self::Replay(poller) => poller.reader.last_time(),
         }
     }
 
@@ -283,14 +780,11 @@ impl MetricsPoller {
         &self,
         start_time: Duration,
         end_time: Duration,
-    ) -> anyhow::Result<impl '_ + Iterator<Item = &Metrics>> {
+    ) -> anyhow::Result<Vec<Metrics>> {
         let Self::Replay(poller) = self else {
             anyhow::bail!("`get_metrics_range()` is only available in replay mode");
         };
-        Ok(poller.metrics_log.iter().filter(move |metrics| {
-            let time = metrics.timestamp;
-            start_time <= time && time <= end_time
-        }))
+        poller.reader.get_range(start_time, end_time)
     }
 }
 
@@ -299,41 +793,68 @@ pub struct Header {
     pub system_version: SystemVersion,
     pub node_name: String,
     pub start_time: chrono::DateTime<chrono::Local>,
+
+    /// Polling interval (not set in record files created by older versions).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub polling_interval: Option<Duration>,
+
+    /// Limits of the `system_info.*_count` metrics (e.g., `process_limit` for `system_info.process_count`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub system_limits: BTreeMap<String, u64>,
+
+    /// Glob patterns of the metrics that cannot be collected (e.g., in read-only mode).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_metrics: Vec<String>,
+
+    /// Headers of the target nodes (only set in cluster mode).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cluster_nodes: Vec<Header>,
+}
+
+impl Header {
+    pub fn is_cluster(&self) -> bool {
+        !self.cluster_nodes.is_empty()
+    }
+
+    /// Returns the headers of the target nodes.
+    ///
+    /// In non-cluster mode, the result only contains `self`.
+    pub fn nodes(&self) -> &[Header] {
+        if self.is_cluster() {
+            &self.cluster_nodes
+        } else {
+            std::slice::from_ref(self)
+        }
+    }
+
+    /// Returns the percentage of the value of the given metric to its limit (if any).
+    pub fn limit_percent(&self, metric_name: &str, value: &MetricValue) -> Option<f64> {
+        let limit = *self.system_limits.get(metric_name)?;
+        let value = value.as_f64()?;
+        (limit > 0).then(|| value / limit as f64 * 100.0)
+    }
+
+    /// Returns the index of the node from which the given metrics were collected.
+    pub fn node_index(&self, metrics: &Metrics) -> Option<usize> {
+        match &metrics.node {
+            None => Some(0),
+            Some(node) => self.nodes().iter().position(|x| x.node_name == *node),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct ReplayMetricsPoller {
     header: Header,
-    metrics_log: Vec<Metrics>,
+    reader: RecordReader,
 }
 
 impl ReplayMetricsPoller {
     fn new(args: ReplayArgs) -> anyhow::Result<Self> {
-        let record_file_path = args.file;
-        let file = std::fs::File::open(&record_file_path).with_context(|| {
-            format!("failed to open record file: {}", record_file_path.display())
-        })?;
-        let reader = std::io::BufReader::new(file);
-
-        let mut header = None;
-        let mut metrics_log = Vec::new();
-        for (i, line) in reader.lines().enumerate() {
-            let line = line?;
-            if i == 0 {
-                header = Some(
-                    serde_json::from_str(&line)
-                        .with_context(|| format!("failed to parse record file: line={}", i + 1))?,
-                );
-                continue;
-            }
-            let metrics = serde_json::from_str(&line)
-                .with_context(|| format!("failed to parse record file: line={}", i + 1))?;
-            metrics_log.push(metrics);
-        }
-        let header = header.ok_or_else(|| anyhow::anyhow!("record file is empty"))?;
+        let reader = RecordReader::open(&args.file)?;
         Ok(Self {
-            header,
-            metrics_log,
+            header: reader.header.clone(),
+            reader,
         })
     }
 }
@@ -342,311 +863,785 @@ impl ReplayMetricsPoller {
 pub struct RealtimeMetricsPoller {
     rx: MetricsReceiver,
     header: Header,
-    rpc_client: RpcClient,
-    old_microstate_accounting_flag: bool,
+    nodes: Vec<NodeMetricsPoller>,
+    recorder: Option<Recorder>,
 }
 
 impl RealtimeMetricsPoller {
-    fn start_thread(args: RunArgs) -> anyhow::Result<Self> {
-        MetricsPollerThread::start_thread(args)
+    fn start_thread(args: RunArgs, collectors: &[CollectorFactory]) -> anyhow::Result<Self> {
+        let cookie = args.connection.find_cookie()?;
+        let config = args.connection.load_config()?;
+
+        // Validates the patterns before connecting to the nodes.
+        MetricFilter::new(&args, &node_collectors(&args, &config, collectors))?;
+        let mut node_names = args.erlang_nodes.clone();
+        anyhow::ensure!(!node_names.is_empty(), "no target Erlang node is specified");
+        anyhow::ensure!(
+            args.connection.port.is_none() || (node_names.len() == 1 && !args.connection.discover),
+            "`--port` option cannot be used with multiple target nodes"
+        );
+
+        if args.connection.discover {
+            let seed_node = node_names[0].clone();
+            let connected_nodes = smol::block_on(async {
+                let client = RpcClient::connect(&seed_node, args.connection.port, &cookie).await?;
+                client.get_nodes().await
+            })
+            .with_context(|| format!("failed to discover nodes connected to {seed_node}"))?;
+            for node in connected_nodes {
+                let node: NodeName = node.parse()?;
+                if !node_names.iter().any(|x| x.to_string() == node.to_string()) {
+                    node_names.push(node);
+                }
+            }
+            log::debug!("discovered nodes: {node_names:?}");
+        }
+        let is_cluster = node_names.len() > 1 || args.connection.discover;
+
+        let (tx, rx) = mpsc::channel();
+        let start_time = chrono::Local::now();
+        let mut nodes = Vec::new();
+        let mut threads = Vec::new();
+        for node_name in &node_names {
+            let target = PollingTarget {
+                node_name,
+                cookie: &cookie,
+                config: &config,
+                collectors: node_collectors(&args, &config, collectors),
+            };
+            let (node, thread) =
+                MetricsPollerThread::new(args.clone(), target, is_cluster, start_time, tx.clone())
+                    .with_context(|| format!("failed to start polling metrics of {node_name}"))?;
+            nodes.push(node);
+            threads.push(thread);
+        }
+
+        let node_headers = threads
+            .iter()
+            .map(|thread| thread.header.clone())
+            .collect::<Vec<_>>();
+        let header = if is_cluster {
+            Header {
+                cluster_nodes: node_headers,
+                ..threads[0].header.clone()
+            }
+        } else {
+            threads[0].header.clone()
+        };
+
+        let recorder = if let Some(path) = &args.record {
+            let recorder = Recorder::create(path, args.record_format)?;
+            smol::block_on(recorder.write_header(&header))?;
+            Some(recorder)
+        } else {
+            None
+        };
+
+        let exporter = args.listen.map(Exporter::start).transpose()?;
+
+        let start = Instant::now();
+        for mut thread in threads {
+            thread.recorder = recorder.clone();
+            thread.exporter = exporter.clone();
+            thread.start = start;
+            thread.prev_metrics = Metrics::new(start);
+            std::thread::spawn(|| thread.run());
+        }
+        Ok(Self {
+            rx,
+            header,
+            nodes,
+            recorder,
+        })
     }
 }
 
+/// Returns the built-in collectors followed by the ones created by `factories`.
+fn node_collectors(
+    args: &RunArgs,
+    config: &Config,
+    factories: &[CollectorFactory],
+) -> Vec<Box<dyn Collector>> {
+    let mut collectors = collector::builtin_collectors(args, config);
+    collectors.extend(factories.iter().map(|factory| factory()));
+    collectors
+}
+
 impl Drop for RealtimeMetricsPoller {
     fn drop(&mut self) {
-        if !self.old_microstate_accounting_flag {
-            if let Err(e) = smol::block_on(
-                self.rpc_client
-                    .set_system_flag_bool("microstate_accounting", "false"),
-            ) {
-                log::warn!("faild to disable microstate accounting: {e}");
+        if let Some(recorder) = &self.recorder {
+            if let Err(e) = smol::block_on(recorder.flush()) {
+                log::warn!("faild to flush record file: {e}");
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct NodeMetricsPoller {
+    connection: Arc<Mutex<Connection>>,
+    subscription: Arc<Mutex<Subscription>>,
+}
+
+impl Drop for NodeMetricsPoller {
+    fn drop(&mut self) {
+        let mut connection = self.connection.lock().expect("unreachable");
+        connection.stopped = true;
+        smol::block_on(connection.restore_system_flags());
+    }
+}
+
+#[derive(Debug)]
+struct Connection {
+    rpc_client: RpcClient,
+
+    // `None` if `erldash` didn't change the flag.
+    old_microstate_accounting_flag: Option<bool>,
+
+    // The process on the target node that keeps `scheduler_wall_time` enabled
+    // (the flag is reference counted per process, so it's disabled when the process exits).
+    scheduler_wall_time_keeper: Option<Pid>,
+
+    // Set when the poller is dropped so that the polling thread doesn't reconnect after that.
+    stopped: bool,
+}
+
+impl Connection {
+    /// System flags are only enabled if the collectors that need them are given.
+    async fn connect(
+        node_name: &NodeName,
+        cookie: &str,
+        args: &RunArgs,
+        collectors: &[Box<dyn Collector>],
+    ) -> anyhow::Result<Self> {
+        let uses = |name: &str| collectors.iter().any(|c| c.name() == name);
+        let mut rpc_client = RpcClient::connect(node_name, args.connection.port, cookie).await?;
+        rpc_client.set_timeout(args.connection.rpc_timeout);
+        let mut connection = Self {
+            rpc_client,
+            old_microstate_accounting_flag: None,
+            scheduler_wall_time_keeper: None,
+            stopped: false,
+        };
+
+        if !args.connection.read_only && uses("msacc") {
+            let old = connection
+                .rpc_client
+                .set_system_flag_bool("microstate_accounting", "true")
+                .await?;
+            log::debug!("enabled microstate accounting (old flag state is {old})");
+            connection.old_microstate_accounting_flag = Some(old);
+        }
+
+        // In read-only mode, `scheduler_wall_time` is enabled only if it's explicitly permitted.
+        let enable_scheduler_wall_time = uses("scheduler_wall_time")
+            && (!args.connection.read_only
+                || (args.connection.enable_scheduler_wall_time
+                    && connection
+                        .rpc_client
+                        .get_statistics_scheduler_wall_time()
+                        .await?
+                        .is_none()));
+        if enable_scheduler_wall_time {
+            let (pid, old) = connection
+                .rpc_client
+                .spawn_system_flag_keeper("scheduler_wall_time")
+                .await?;
+            log::debug!("enabled scheduler_wall_time by {pid} (old flag state is {old})");
+            connection.scheduler_wall_time_keeper = Some(pid);
+        }
+        Ok(connection)
+    }
+
+    /// Restores the system flags changed by [`Connection::connect()`].
+    async fn restore_system_flags(&mut self) {
+        if self.old_microstate_accounting_flag == Some(false) {
+            if let Err(e) = self
+                .rpc_client
+                .set_system_flag_bool("microstate_accounting", "false")
+                .await
+            {
+                log::warn!("faild to disable microstate_accounting: {e}");
+            } else {
+                log::debug!("disabled microstate_accounting");
+            }
+        }
+        if let Some(pid) = self.scheduler_wall_time_keeper.take() {
+            if let Err(e) = self.rpc_client.kill_process(pid).await {
+                log::warn!("faild to kill the process keeping scheduler_wall_time enabled: {e}");
             } else {
-                log::debug!("disabled microstate accounting");
+                log::debug!("killed the process keeping scheduler_wall_time enabled");
             }
         }
     }
+
+    /// Returns the glob patterns of the metrics that cannot be collected via this connection.
+    async fn unavailable_metrics(&self, args: &RunArgs) -> anyhow::Result<Vec<String>> {
+        let mut unavailable_metrics = Vec::new();
+        let scheduler_wall_time = self.rpc_client.get_statistics_scheduler_wall_time().await?;
+        if scheduler_wall_time.is_none() {
+            log::warn!(
+                "scheduler_wall_time is disabled, so scheduler utilization metrics are unavailable"
+            );
+            if args.connection.read_only {
+                unavailable_metrics.push("utilization.*".to_owned());
+            }
+            unavailable_metrics.push("scheduler_wall_time*".to_owned());
+        } else if args.connection.read_only {
+            unavailable_metrics.extend(READ_ONLY_UNAVAILABLE_METRICS.iter().map(|x| x.to_string()));
+        }
+        Ok(unavailable_metrics)
+    }
+}
+
+/// Metrics that require microstate accounting.
+const READ_ONLY_UNAVAILABLE_METRICS: &[&str] = &[
+    "utilization.*.state.*",
+    "statistics.garbage_collection.time_share",
+    "utilization.aux",
+    "utilization.async",
+    "utilization.poll",
+    "utilization.sys",
+];
+
+/// A target node and how to collect its metrics.
+#[derive(Debug)]
+struct PollingTarget<'a> {
+    node_name: &'a NodeName,
+    cookie: &'a str,
+    config: &'a Config,
+    collectors: Vec<Box<dyn Collector>>,
 }
 
 #[derive(Debug)]
 struct MetricsPollerThread {
     args: RunArgs,
+    node_name: NodeName,
+    cookie: String,
+    connection: Arc<Mutex<Connection>>,
     rpc_client: RpcClient,
     tx: MetricsSender,
     prev_metrics: Metrics,
     start: Instant,
     header: Header,
-    record_file: Option<File>,
+    recorder: Option<Recorder>,
+    exporter: Option<Exporter>,
+    alert_evaluator: AlertEvaluator,
+    subscription: Arc<Mutex<Subscription>>,
+    collectors: Vec<Box<dyn Collector>>,
+    filter: MetricFilter,
+    wordsize: u64,
+
+    // Whether to evaluate the RPCs in a batch (disabled if the target node doesn't support it).
+    batch_rpc: bool,
+
+    // The total number of collector failures.
+    error_count: u64,
+
+    /// The node name attached to each metrics (only set in cluster mode).
+    node: Option<String>,
 }
 
 impl MetricsPollerThread {
-    fn start_thread(args: RunArgs) -> anyhow::Result<RealtimeMetricsPoller> {
-        let (tx, rx) = mpsc::channel();
-
-        let rpc_client: RpcClient = smol::block_on(async {
-            let cookie = args.find_cookie()?;
-            let client = RpcClient::connect(&args.erlang_node, args.port, &cookie).await?;
-            Ok(client) as anyhow::Result<_>
-        })?;
-        let system_version = smol::block_on(rpc_client.get_system_version())?;
-        let old_microstate_accounting_flag =
-            smol::block_on(rpc_client.set_system_flag_bool("microstate_accounting", "true"))?;
+    fn new(
+        args: RunArgs,
+        target: PollingTarget,
+        is_cluster: bool,
+        start_time: chrono::DateTime<chrono::Local>,
+        tx: MetricsSender,
+    ) -> anyhow::Result<(NodeMetricsPoller, Self)> {
+        let PollingTarget {
+            node_name,
+            cookie,
+            config,
+            mut collectors,
+        } = target;
+        let filter = MetricFilter::new(&args, &collectors)?;
+        collectors.retain(|collector| filter.is_collector_selected(collector.as_ref()));
         log::debug!(
-            "enabled microstate accounting (old flag state is {old_microstate_accounting_flag})"
+            "collectors of {node_name}: {:?}",
+            collectors.iter().map(|c| c.name()).collect::<Vec<_>>()
         );
 
+        let connection =
+            smol::block_on(Connection::connect(node_name, cookie, &args, &collectors))?;
+        let rpc_client = connection.rpc_client.clone();
+        let system_version = smol::block_on(rpc_client.get_system_version())?;
+        let wordsize = smol::block_on(init_collectors(&mut collectors, &rpc_client))?;
+        let mut unavailable_metrics = smol::block_on(connection.unavailable_metrics(&args))?;
+        unavailable_metrics.retain(|pattern| filter.is_selected(pattern));
+        let system_limits = smol::block_on(get_system_limits(&rpc_client));
+
+        let connection = Arc::new(Mutex::new(connection));
+        let subscription = Arc::new(Mutex::new(Subscription::default()));
         let header = Header {
-            system_version: system_version.clone(),
-            node_name: args.erlang_node.to_string(),
-            start_time: chrono::Local::now(),
+            system_version,
+            node_name: node_name.to_string(),
+            start_time,
+            polling_interval: Some(args.connection.polling_interval),
+            system_limits,
+            unavailable_metrics,
+            cluster_nodes: Vec::new(),
         };
-        let poller = RealtimeMetricsPoller {
-            rx,
-            header: header.clone(),
-            rpc_client: rpc_client.clone(),
-            old_microstate_accounting_flag,
+        let node = NodeMetricsPoller {
+            connection: connection.clone(),
+            subscription: subscription.clone(),
         };
 
-        let record_file = if let Some(path) = &args.record {
-            Some(File::from(std::fs::File::create(path).with_context(
-                || format!("failed to record file {}", path.display()),
-            )?))
-        } else {
-            None
+        let start = Instant::now();
+        let thread = Self {
+            args,
+            node_name: node_name.clone(),
+            cookie: cookie.to_owned(),
+            connection,
+            rpc_client,
+            tx,
+            prev_metrics: Metrics::new(start),
+            start,
+            header,
+            recorder: None,
+            exporter: None,
+            alert_evaluator: AlertEvaluator::new(config, node_name.to_string(), start_time),
+            subscription,
+            collectors,
+            filter,
+            wordsize,
+            batch_rpc: true,
+            error_count: 0,
+            node: is_cluster.then(|| node_name.to_string()),
         };
+        Ok((node, thread))
+    }
 
-        std::thread::spawn(|| {
-            let start = Instant::now();
-            Self {
-                args,
-                rpc_client,
-                tx,
-                prev_metrics: Metrics::new(start),
-                start,
-                header,
-                record_file,
-            }
-            .run()
-        });
-        Ok(poller)
+    /// Retries connecting to the target node until it succeeds.
+    ///
+    /// Returns `false` if the poller has been stopped (i.e., [`NodeMetricsPoller`] was dropped) in the meantime.
+    async fn reconnect(&mut self) -> bool {
+        let mut backoff = MIN_RECONNECT_BACKOFF;
+        loop {
+            std::thread::sleep(backoff);
+            if self.is_stopped() {
+                return false;
+            }
+            log::info!("reconnecting to {}", self.node_name);
+            match self.try_reconnect().await {
+                Ok(false) => return false,
+                Ok(true) => {
+                    log::info!("reconnected to {}", self.node_name);
+                    return true;
+                }
+                Err(e) => {
+                    log::warn!("faild to reconnect to {}: {e}", self.node_name);
+                    backoff = std::cmp::min(backoff * 2, MAX_RECONNECT_BACKOFF);
+                }
+            }
+        }
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.connection.lock().expect("unreachable").stopped
     }
 
-    async fn write_json_line(&mut self, value: &impl serde::Serialize) -> anyhow::Result<()> {
-        if let Some(file) = &mut self.record_file {
-            let mut bytes = serde_json::to_vec(value)?;
-            bytes.push(b'\n');
-            file.write_all(&bytes).await?;
-            file.flush().await?;
+    async fn try_reconnect(&mut self) -> anyhow::Result<bool> {
+        let mut connection =
+            Connection::connect(&self.node_name, &self.cookie, &self.args, &self.collectors)
+                .await?;
+        self.wordsize = init_collectors(&mut self.collectors, &connection.rpc_client).await?;
+        self.rpc_client = connection.rpc_client.clone();
+
+        // The flags read via the new connection may have been changed by `erldash` itself
+        // (e.g., after a network failure), so the values read first are restored on exit.
+        let mut current = self.connection.lock().expect("unreachable");
+        if current.stopped {
+            // The poller was dropped while connecting, so nobody else restores the flags.
+            drop(current);
+            connection.restore_system_flags().await;
+            return Ok(false);
+        }
+        current.old_microstate_accounting_flag = current
+            .old_microstate_accounting_flag
+            .or(connection.old_microstate_accounting_flag);
+        let old_keeper = std::mem::replace(
+            &mut current.scheduler_wall_time_keeper,
+            connection.scheduler_wall_time_keeper,
+        );
+        current.rpc_client = connection.rpc_client;
+        drop(current);
+
+        // The old keeper process exits by itself if the old connection was lost, but it may still be alive.
+        if let Some(pid) = old_keeper {
+            if let Err(e) = self.rpc_client.kill_process(pid).await {
+                log::warn!(
+                    "faild to kill the old process keeping scheduler_wall_time enabled: {e}"
+                );
+            }
+        }
+
+        // Counters may have been reset if the node restarted, and the interval is too long anyway.
+        self.prev_metrics = Metrics::new(self.start);
+        self.batch_rpc = true;
+        Ok(true)
+    }
+
+    async fn write_metrics(&mut self, metrics: &Metrics) -> anyhow::Result<()> {
+        if let Some(recorder) = &self.recorder {
+            recorder.write_metrics(metrics).await?;
         }
         Ok(())
     }
 
     fn run(mut self) {
-        let interval = Duration::from_secs(self.args.polling_interval.get() as u64);
+        let interval = self.args.connection.polling_interval;
         let mut next_time = Duration::from_secs(0);
         smol::block_on(async {
-            if let Err(e) = self.write_json_line(&self.header.clone()).await {
-                log::error!("faild to write record file: {e}");
-                return;
-            }
-
             loop {
-                match self.poll_once().await {
+                let mut metrics = match self.poll_once().await {
                     Err(e) => {
                         log::error!("faild to poll metrics: {e}");
-                        break;
+                        Metrics::disconnected_marker(self.start, self.node.clone())
                     }
-                    Ok(metrics) => {
-                        let elapsed = metrics.timestamp;
-
-                        if let Err(e) = self.write_json_line(&metrics).await {
-                            log::error!("faild to write record file: {e}");
-                            break;
-                        }
-
-                        if self.tx.send(metrics).is_err() {
-                            log::debug!("the main thread has terminated");
-                            break;
-                        }
-
-                        next_time += interval;
-                        if let Some(sleep_duration) = next_time.checked_sub(elapsed) {
-                            std::thread::sleep(sleep_duration);
-                        }
+                    Ok(metrics) => metrics,
+                };
+                metrics.alerts = self.alert_evaluator.evaluate(&metrics);
+                let poll_duration = self.start.elapsed().saturating_sub(metrics.timestamp);
+                if poll_duration > interval && !metrics.disconnected {
+                    log::warn!(
+                        "polling metrics of {} took {poll_duration:?} (interval is {interval:?})",
+                        self.node_name
+                    );
+                    metrics.slow_poll = Some(poll_duration);
+                }
+                let disconnected = metrics.disconnected;
+
+                if let Err(e) = self.write_metrics(&metrics).await {
+                    log::error!("faild to write record file: {e}");
+                    break;
+                }
+
+                if let Some(exporter) = &self.exporter {
+                    exporter.update(&self.header.node_name, &metrics);
+                }
+
+                if self.tx.send(metrics).is_err() {
+                    log::debug!("the main thread has terminated");
+                    break;
+                }
+
+                if disconnected {
+                    if !self.reconnect().await {
+                        log::debug!("the poller of {} has been stopped", self.node_name);
+                        break;
                     }
+                    next_time = self.start.elapsed();
+                    continue;
+                }
+
+                next_time += interval;
+                let now = self.start.elapsed();
+                if let Some(sleep_duration) = next_time.checked_sub(now) {
+                    std::thread::sleep(sleep_duration);
+                } else {
+                    // Skips the missed ticks instead of polling repeatedly to catch up.
+                    next_time = now;
                 }
             }
         })
     }
 
-    fn insert_msacc_metrics(&self, metrics: &mut Metrics, msacc_threads: &[MSAccThread]) {
-        let mut aggregated_per_type = BTreeMap::<_, ThreadTime>::new();
-        let mut aggregated_per_state_per_type = BTreeMap::<_, BTreeMap<&str, u64>>::new();
-        let mut aggregated_per_thread_per_type = BTreeMap::<_, BTreeMap<u64, ThreadTime>>::new();
-
-        for thread in msacc_threads {
-            let x = aggregated_per_type.entry(&thread.thread_type).or_default();
-            let realtime = thread.counters.values().copied().sum::<u64>();
-            let sleeptime = thread.counters["sleep"];
-            x.realtime += realtime;
-            x.runtime += realtime - sleeptime;
-
-            let x = aggregated_per_thread_per_type
-                .entry(&thread.thread_type)
-                .or_default()
-                .entry(thread.thread_id)
-                .or_default();
-            x.realtime += realtime;
-            x.runtime += realtime - sleeptime;
-
-            for (state, value) in &thread.counters {
-                *aggregated_per_state_per_type
-                    .entry(&thread.thread_type)
-                    .or_default()
-                    .entry(state)
-                    .or_default() += *value;
-            }
-        }
-        for (ty, time) in aggregated_per_type {
-            let root_name = format!("utilization.{ty}");
-            metrics.insert(&root_name, MetricValue::utilization(time.utilization()));
-            for (state, value) in &aggregated_per_state_per_type[ty] {
-                let u = *value as f64 / time.realtime as f64 * 100.0;
-                metrics.insert(
-                    &format!("{root_name}.state.{state}"),
-                    MetricValue::utilization_with_parent(u, &root_name),
+    /// Returns the round-trip time of the batched RPC if it succeeded.
+    async fn prefetch(&mut self) -> Option<Duration> {
+        if !self.batch_rpc {
+            return None;
+        }
+        let calls = self
+            .collectors
+            .iter()
+            .flat_map(|collector| collector.batch_calls())
+            .collect();
+        let start = Instant::now();
+        match self.rpc_client.prefetch(calls).await {
+            Ok(()) => Some(start.elapsed()),
+            Err(e) if e.is::<RpcTimeoutError>() || !self.rpc_client.is_connected() => {
+                // Transient failures (the batch is retried in the next poll).
+                log::warn!(
+                    "faild to batch RPCs to {} (falling back to individual calls in this poll): {e}",
+                    self.node_name
                 );
+                None
             }
-
-            let id_width = aggregated_per_thread_per_type[ty]
-                .keys()
-                .map(|id| id / 10 + 1)
-                .max()
-                .unwrap_or(1) as usize;
-            for (thread_id, time) in &aggregated_per_thread_per_type[ty] {
-                metrics.insert(
-                    &format!("{root_name}.thread.{:0id_width$}", thread_id),
-                    MetricValue::utilization_with_parent(time.utilization(), &root_name),
+            Err(e) => {
+                // E.g., `erl_eval` is unavailable or the reply has an unexpected shape.
+                log::warn!(
+                    "faild to batch RPCs to {} (falling back to individual calls): {e}",
+                    self.node_name
                 );
+                self.batch_rpc = false;
+                None
             }
         }
     }
 
     async fn poll_once(&mut self) -> anyhow::Result<Metrics> {
         let mut metrics = Metrics::new(self.start);
+        metrics.node = self.node.clone();
+
+        // Discards the latencies of the RPCs made outside of the previous poll.
+        self.rpc_client.take_min_latency();
+        let batch_latency = self.prefetch().await;
+        self.ensure_connected()?;
+        self.run_collectors(&mut metrics).await;
+        self.rpc_client.clear_prefetched();
+        self.ensure_connected()?;
+
+        // If the RPCs are not batched, the shortest round-trip time of the individual calls is reported instead.
+        if let Some(rpc_latency) = batch_latency.or_else(|| self.rpc_client.take_min_latency()) {
+            metrics.insert(
+                "poll.rpc_latency_us",
+                MetricValue::gauge(rpc_latency.as_micros() as u64),
+            );
+        }
 
-        let msacc = self
-            .rpc_client
-            .get_statistics_microstate_accounting()
-            .await?;
-        self.insert_msacc_metrics(&mut metrics, &msacc);
+        let subscription = self.subscription.lock().expect("unreachable").clone();
+        if subscription.processes {
+            match self.rpc_client.get_process_info_all().await {
+                Ok(processes) => {
+                    metrics.processes = processes
+                        .into_iter()
+                        .map(|info| ProcessMetrics::new(info, self.wordsize))
+                        .collect();
+                }
+                Err(e) => self.record_error(&mut metrics, "processes", &e),
+            }
+        }
+        if subscription.ports {
+            match self.rpc_client.get_port_info_all().await {
+                Ok(ports) => {
+                    metrics.ports = ports.into_iter().map(PortMetrics::new).collect();
+                }
+                Err(e) => self.record_error(&mut metrics, "ports", &e),
+            }
+        }
+        if subscription.ets_tables {
+            match self.rpc_client.get_ets_tables().await {
+                Ok(tables) => {
+                    metrics.ets_tables = tables
+                        .into_iter()
+                        .map(|info| EtsTableMetrics::new(info, self.wordsize))
+                        .collect();
+                }
+                Err(e) => self.record_error(&mut metrics, "ets_tables", &e),
+            }
+        }
+        if let Some(pid) = subscription.process_detail {
+            match self.rpc_client.get_process_detail(pid).await {
+                Ok(detail) => metrics.process_detail = detail,
+                Err(e) => self.record_error(&mut metrics, "process_detail", &e),
+            }
+        }
 
-        let processes = self.rpc_client.get_system_info_u64("process_count").await?;
-        metrics.insert("system_info.process_count", MetricValue::gauge(processes));
+        metrics.insert("poll.errors", MetricValue::counter(self.error_count));
+        if !self.filter.is_empty() {
+            metrics
+                .items
+                .retain(|name, _| self.filter.is_selected(name));
+        }
+        if !metrics.errors.is_empty() {
+            metrics.missing = self
+                .prev_metrics
+                .root_items()
+                .filter(|(name, _)| !metrics.items.contains_key(*name))
+                .map(|(name, _)| name.to_owned())
+                .collect();
+        }
 
-        let ports = self.rpc_client.get_system_info_u64("port_count").await?;
-        metrics.insert("system_info.port_count", MetricValue::gauge(ports));
+        log::debug!(
+            "MetricsPoller::poll_once(): elapsed={:?}",
+            metrics.timestamp
+        );
+        metrics.calc_delta(&self.prev_metrics);
 
-        let atoms = self.rpc_client.get_system_info_u64("atom_count").await?;
-        metrics.insert("system_info.atom_count", MetricValue::gauge(atoms));
+        self.prev_metrics = metrics.clone();
 
-        let ets_tables = self.rpc_client.get_system_info_u64("ets_count").await?;
-        metrics.insert("system_info.ets_count", MetricValue::gauge(ets_tables));
+        Ok(metrics)
+    }
 
-        let context_switches = self
-            .rpc_client
-            .get_statistics_1st_u64("context_switches")
-            .await?;
-        metrics.insert(
-            "statistics.context_switches",
-            MetricValue::counter(context_switches),
+    /// Returns an error if the connection to the target node has been lost.
+    ///
+    /// Failures of collectors (e.g., timeouts) are not considered as disconnection.
+    fn ensure_connected(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.rpc_client.is_connected(),
+            "lost connection to {}",
+            self.node_name
         );
+        Ok(())
+    }
 
-        let exact_reductions = self
-            .rpc_client
-            .get_statistics_1st_u64("exact_reductions")
-            .await?;
-        metrics.insert(
-            "statistics.exact_reductions",
-            MetricValue::counter(exact_reductions),
-        );
+    /// Runs the collectors whose RPCs may have been prefetched by [`Self::prefetch()`].
+    ///
+    /// A failure of a collector doesn't prevent the others from collecting metrics.
+    async fn run_collectors(&mut self, metrics: &mut Metrics) {
+        let mut errors = Vec::new();
+        for collector in &mut self.collectors {
+            if let Err(e) = collector
+                .collect(&self.rpc_client, &self.prev_metrics, metrics)
+                .await
+            {
+                errors.push((collector.name().to_owned(), e));
+            }
+        }
+        for (collector, e) in errors {
+            self.record_error(metrics, &collector, &e);
+        }
+    }
 
-        let garbage_collection = self
-            .rpc_client
-            .get_statistics_1st_u64("garbage_collection")
-            .await?;
-        metrics.insert(
-            "statistics.garbage_collection",
-            MetricValue::counter(garbage_collection),
+    fn record_error(&mut self, metrics: &mut Metrics, collector: &str, error: &anyhow::Error) {
+        log::warn!(
+            "faild to collect {collector} metrics of {}: {error}",
+            self.node_name
         );
+        self.error_count += 1;
+        metrics.errors.push(CollectorError {
+            collector: collector.to_owned(),
+            message: error.to_string(),
+        });
+    }
+}
 
-        let runtime = self.rpc_client.get_statistics_1st_u64("runtime").await?;
-        metrics.insert("statistics.runtime", MetricValue::counter(runtime));
+/// Initializes the collectors and returns the word size of the target node.
+async fn init_collectors(
+    collectors: &mut [Box<dyn Collector>],
+    rpc_client: &RpcClient,
+) -> anyhow::Result<u64> {
+    for collector in collectors {
+        collector
+            .init(rpc_client)
+            .await
+            .with_context(|| format!("failed to initialize {} collector", collector.name()))?;
+    }
+    rpc_client.get_system_info_u64("wordsize").await
+}
 
-        let (in_bytes, out_bytes) = self.rpc_client.get_statistics_io().await?;
-        metrics.insert(
-            "statistics.io.total_bytes",
-            MetricValue::counter(in_bytes + out_bytes),
-        );
-        metrics.insert(
-            "statistics.io.input_bytes",
-            MetricValue::counter_with_parent(in_bytes, "statistics.io.total_bytes"),
-        );
-        metrics.insert(
-            "statistics.io.output_bytes",
-            MetricValue::counter_with_parent(out_bytes, "statistics.io.total_bytes"),
-        );
+/// Returns the limits of the `system_info.*_count` metrics.
+///
+/// Limits not supported by the target node are omitted.
+async fn get_system_limits(rpc_client: &RpcClient) -> BTreeMap<String, u64> {
+    let mut limits = BTreeMap::new();
+    for name in ["process", "port", "atom", "ets"] {
+        match rpc_client
+            .get_system_info_u64(&format!("{name}_limit"))
+            .await
+        {
+            Ok(limit) => {
+                limits.insert(format!("system_info.{name}_count"), limit);
+            }
+            Err(e) => {
+                log::debug!("faild to get {name}_limit: {e}");
+            }
+        }
+    }
+    limits
+}
 
-        let run_queue_lengths = self
-            .rpc_client
-            .get_statistics_u64_list("run_queue_lengths_all")
-            .await?;
-        let run_queue_total = run_queue_lengths.iter().copied().sum();
-        metrics.insert("statistics.run_queue", MetricValue::gauge(run_queue_total));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
 
-        let width = run_queue_lengths.len() / 10 + 1;
-        for (i, n) in run_queue_lengths.into_iter().enumerate() {
-            metrics.insert(
-                &format!("statistics.run_queue.{:0width$}", i),
-                MetricValue::gauge_with_parent(n, "statistics.run_queue"),
-            );
-        }
+    #[derive(Debug, Parser)]
+    struct Cli {
+        #[clap(flatten)]
+        args: RunArgs,
+    }
 
-        let mut memory = self.rpc_client.get_memory().await?;
-        metrics.insert(
-            "memory.total_bytes",
-            MetricValue::gauge(memory.remove("total").expect("unreachable")),
-        );
-        for (k, v) in memory {
-            metrics.insert(
-                &format!("memory.{k}_bytes"),
-                MetricValue::gauge_with_parent(v, "memory.total_bytes"),
-            );
-        }
+    fn run_args(options: &[&str]) -> RunArgs {
+        let mut argv = vec!["erldash", "foo@localhost"];
+        argv.extend_from_slice(options);
+        Cli::parse_from(argv).args
+    }
 
-        self.rpc_client
-            .set_system_flag_bool("microstate_accounting", "reset")
-            .await?;
+    fn selected_collectors(options: &[&str]) -> anyhow::Result<Vec<String>> {
+        let args = run_args(options);
+        let collectors = collector::builtin_collectors(&args, &Config::default());
+        let filter = MetricFilter::new(&args, &collectors)?;
+        Ok(collectors
+            .iter()
+            .filter(|c| filter.is_collector_selected(c.as_ref()))
+            .map(|c| c.name().to_owned())
+            .collect())
+    }
 
-        log::debug!(
-            "MetricsPoller::poll_once(): elapsed={:?}",
-            metrics.timestamp
-        );
-        metrics.calc_delta(&self.prev_metrics);
+    #[test]
+    fn glob_match_works() {
+        assert!(glob_match("memory.*", "memory.total_bytes"));
+        assert!(glob_match("memory.*", "memory.ets_bytes"));
+        assert!(!glob_match("memory.*", "memory"));
+        assert!(glob_match(
+            "utilization.*",
+            "utilization.scheduler.thread.1"
+        ));
+        assert!(glob_match(
+            "utilization.*.state.?",
+            "utilization.aux.state.a"
+        ));
+        assert!(!glob_match("utilization.*", "scheduler_wall_time"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("", "memory"));
+    }
 
-        self.prev_metrics = metrics.clone();
+    #[test]
+    fn globs_overlap_works() {
+        assert!(globs_overlap("utilization.*", "utilization.*"));
+        assert!(globs_overlap("utilization.scheduler", "utilization.*"));
+        assert!(globs_overlap("*.total_bytes", "memory.*"));
+        assert!(globs_overlap("memory.???_bytes", "memory.*"));
+        assert!(!globs_overlap("memory.*", "utilization.*"));
+        assert!(!globs_overlap("memroy", "memory.*"));
+    }
 
-        Ok(metrics)
+    #[test]
+    fn metric_filter_selects_descendants() {
+        let args = run_args(&["--metrics", "memory.total_bytes"]);
+        let collectors = collector::builtin_collectors(&args, &Config::default());
+        let filter = MetricFilter::new(&args, &collectors).expect("unreachable");
+        assert!(filter.is_selected("memory.total_bytes"));
+        assert!(filter.is_selected("memory.total_bytes.ets_bytes"));
+        assert!(!filter.is_selected("memory.total_bytesx"));
+        assert!(!filter.is_selected("utilization.scheduler"));
     }
-}
 
-#[derive(Debug, Default)]
-struct ThreadTime {
-    runtime: u64,
-    realtime: u64,
-}
+    #[test]
+    fn metric_filter_selects_collectors() {
+        let all = selected_collectors(&[]).expect("unreachable");
+        assert!(all.iter().any(|c| c == "msacc"));
+
+        let selected = selected_collectors(&["--metrics", "memory.*"]).expect("unreachable");
+        assert_eq!(selected, ["memory"]);
+
+        let selected = selected_collectors(&["--metrics", "memory"]).expect("unreachable");
+        assert_eq!(selected, ["memory"]);
+
+        let selected =
+            selected_collectors(&["--metrics", "utilization.*", "--metrics", "statistics"])
+                .expect("unreachable");
+        assert_eq!(selected, ["msacc", "statistics"]);
+
+        // Excluding `utilization.*` disables microstate accounting.
+        let selected =
+            selected_collectors(&["--exclude-metrics", "utilization.*"]).expect("unreachable");
+        assert!(!selected.iter().any(|c| c == "msacc"));
+        assert!(selected.iter().any(|c| c == "statistics"));
+
+        let selected = selected_collectors(&["--exclude-metrics", "msacc"]).expect("unreachable");
+        assert!(!selected.iter().any(|c| c == "msacc"));
+        assert_eq!(selected.len(), all.len() - 1);
+    }
 
-impl ThreadTime {
-    fn utilization(&self) -> f64 {
-        self.runtime as f64 / self.realtime as f64 * 100.0
+    #[test]
+    fn metric_filter_rejects_patterns_selecting_nothing() {
+        assert!(selected_collectors(&["--metrics", "memroy"]).is_err());
+        assert!(selected_collectors(&["--exclude-metrics", "*"]).is_err());
+        assert!(selected_collectors(&["--metrics", "poll.*"]).is_err());
+        assert!(selected_collectors(&["--metrics", "poll.errors", "--metrics", "ets"]).is_ok());
     }
 }
@@ -0,0 +1,921 @@
+//! Record files.
+//!
+//! Two formats are supported:
+//! - JSON lines: the first line is a [`Header`] and each of the following lines is a [`Metrics`].
+//! - Binary: a compact format for long captures (see below).
+//!
+//! The binary format starts with [`MAGIC`], a version byte and a length-prefixed JSON [`Header`].
+//! The rest of the file is a sequence of chunks, each of which holds up to [`CHUNK_SAMPLES`] samples:
+//!
+//! ```text
+//! chunk = min_timestamp_us, span_us, sample_count, body_len, body
+//! body  = string_count, string*, sample*
+//! ```
+//!
+//! All integers are LEB128 varints.
+//! Metric and node names are stored once per chunk in the string table and referred to by index.
+//! Integer values are delta-encoded (zigzag) and float values are XOR-encoded against the previous
+//! value of the same metric in the same chunk.
+//! Chunks are self-contained, so a reader only needs to scan the chunk headers to build a time index
+//! and can decode each chunk lazily.
+use crate::metrics::{Header, MetricValue, Metrics};
+use crate::RecordFormat;
+use anyhow::Context;
+use smol::fs::File;
+use smol::io::AsyncWriteExt;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{BufRead, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const MAGIC: &[u8; 7] = b"ERLDASH";
+const VERSION: u8 = 1;
+pub const CHUNK_SAMPLES: usize = 64;
+const CHUNK_CACHE_SIZE: usize = 4;
+
+const KIND_GAUGE: u8 = 0;
+const KIND_COUNTER: u8 = 1;
+const KIND_UTILIZATION: u8 = 2;
+const FLAG_HAS_PARENT: u8 = 0b0100;
+const FLAG_HAS_VALUE: u8 = 0b1000;
+
+const SAMPLE_DISCONNECTED: u8 = 0b01;
+const SAMPLE_HAS_NODE: u8 = 0b10;
+
+/// Writes a record file that can be shared by multiple polling threads.
+///
+/// In the binary format, samples are buffered until a chunk is filled,
+/// so [`Recorder::flush()`] needs to be called before exiting.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    inner: Arc<smol::lock::Mutex<RecorderInner>>,
+}
+
+#[derive(Debug)]
+struct RecorderInner {
+    file: File,
+    format: RecordFormat,
+    pending: ChunkEncoder,
+    written_bytes: u64,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, format: RecordFormat) -> anyhow::Result<Self> {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("failed to record file {}", path.display()))?;
+        Ok(Self {
+            inner: Arc::new(smol::lock::Mutex::new(RecorderInner {
+                file: File::from(file),
+                format,
+                pending: ChunkEncoder::default(),
+                written_bytes: 0,
+            })),
+        })
+    }
+
+    pub async fn write_header(&self, header: &Header) -> anyhow::Result<()> {
+        let mut inner = self.inner.lock().await;
+        let bytes = match inner.format {
+            RecordFormat::Json => json_line(header)?,
+            RecordFormat::Binary => {
+                let mut bytes = MAGIC.to_vec();
+                bytes.push(VERSION);
+                let json = serde_json::to_vec(header)?;
+                write_varint(&mut bytes, json.len() as u64);
+                bytes.extend_from_slice(&json);
+                bytes
+            }
+        };
+        inner.write_all(&bytes).await
+    }
+
+    pub async fn write_metrics(&self, metrics: &Metrics) -> anyhow::Result<()> {
+        let mut inner = self.inner.lock().await;
+        match inner.format {
+            RecordFormat::Json => {
+                let bytes = json_line(metrics)?;
+                inner.write_all(&bytes).await
+            }
+            RecordFormat::Binary => {
+                inner.pending.push(metrics);
+                if inner.pending.sample_count >= CHUNK_SAMPLES {
+                    inner.flush_chunk().await?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn flush(&self) -> anyhow::Result<()> {
+        self.inner.lock().await.flush_chunk().await
+    }
+
+    /// Returns the size of the recorded data in bytes, including the samples not flushed yet.
+    pub async fn size(&self) -> u64 {
+        let inner = self.inner.lock().await;
+        let pending_bytes = if inner.pending.is_empty() {
+            0
+        } else {
+            inner.pending.len()
+        };
+        inner.written_bytes + pending_bytes
+    }
+}
+
+impl RecorderInner {
+    async fn write_all(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
+        self.file.write_all(bytes).await?;
+        self.file.flush().await?;
+        self.written_bytes += bytes.len() as u64;
+        Ok(())
+    }
+
+    async fn flush_chunk(&mut self) -> anyhow::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let bytes = std::mem::take(&mut self.pending).finish();
+        self.write_all(&bytes).await
+    }
+}
+
+fn json_line(value: &impl serde::Serialize) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = serde_json::to_vec(value)?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+/// Loads the header and all the metrics stored in a record file.
+pub fn load_record_file(path: &Path) -> anyhow::Result<(Header, Vec<Metrics>)> {
+    let reader = RecordReader::open(path)?;
+    let metrics_log = reader.read_all()?;
+    Ok((reader.header, metrics_log))
+}
+
+/// Reads a record file.
+///
+/// JSON lines files are fully loaded on open, while binary files are decoded lazily chunk by chunk.
+#[derive(Debug)]
+pub struct RecordReader {
+    pub header: Header,
+    log: RecordLog,
+}
+
+#[derive(Debug)]
+enum RecordLog {
+    Json(Vec<Metrics>),
+    Binary(BinaryLog),
+}
+
+impl RecordReader {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let mut file = std::fs::File::open(path)
+            .with_context(|| format!("failed to open record file: {}", path.display()))?;
+        let mut magic = [0; MAGIC.len()];
+        let is_binary = file.read_exact(&mut magic).is_ok() && magic == *MAGIC;
+        file.seek(SeekFrom::Start(0))?;
+
+        if is_binary {
+            let (header, log) = BinaryLog::open(file)?;
+            Ok(Self {
+                header,
+                log: RecordLog::Binary(log),
+            })
+        } else {
+            let (header, metrics_log) = load_json_lines(file)?;
+            Ok(Self {
+                header,
+                log: RecordLog::Json(metrics_log),
+            })
+        }
+    }
+
+    pub fn last_time(&self) -> Duration {
+        match &self.log {
+            RecordLog::Json(metrics_log) => {
+                metrics_log.last().map(|m| m.timestamp).unwrap_or_default()
+            }
+            RecordLog::Binary(log) => log.chunks.last().map(|c| c.end).unwrap_or_default(),
+        }
+    }
+
+    /// Returns the metrics whose timestamps are within `start_time..=end_time`.
+    pub fn get_range(
+        &self,
+        start_time: Duration,
+        end_time: Duration,
+    ) -> anyhow::Result<Vec<Metrics>> {
+        let in_range = |metrics: &Metrics| {
+            let time = metrics.timestamp;
+            start_time <= time && time <= end_time
+        };
+        match &self.log {
+            RecordLog::Json(metrics_log) => Ok(metrics_log
+                .iter()
+                .filter(|m| in_range(m))
+                .cloned()
+                .collect()),
+            RecordLog::Binary(log) => {
+                let mut result = Vec::new();
+                for (i, chunk) in log.chunks.iter().enumerate() {
+                    if chunk.end < start_time || end_time < chunk.start {
+                        continue;
+                    }
+                    let samples = log.read_chunk(i)?;
+                    result.extend(samples.iter().filter(|m| in_range(m)).cloned());
+                }
+                result.sort_by_key(|m| m.timestamp);
+                Ok(result)
+            }
+        }
+    }
+
+    pub fn read_all(&self) -> anyhow::Result<Vec<Metrics>> {
+        match &self.log {
+            RecordLog::Json(metrics_log) => Ok(metrics_log.clone()),
+            RecordLog::Binary(log) => {
+                let mut result = Vec::new();
+                for i in 0..log.chunks.len() {
+                    result.extend(log.read_chunk(i)?.iter().cloned());
+                }
+                Ok(result)
+            }
+        }
+    }
+}
+
+fn load_json_lines(file: std::fs::File) -> anyhow::Result<(Header, Vec<Metrics>)> {
+    let reader = std::io::BufReader::new(file);
+
+    let mut header = None;
+    let mut metrics_log = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if i == 0 {
+            header = Some(
+                serde_json::from_str(&line)
+                    .with_context(|| format!("failed to parse record file: line={}", i + 1))?,
+            );
+            continue;
+        }
+        let metrics = serde_json::from_str(&line)
+            .with_context(|| format!("failed to parse record file: line={}", i + 1))?;
+        metrics_log.push(metrics);
+    }
+    let header = header.ok_or_else(|| anyhow::anyhow!("record file is empty"))?;
+    Ok((header, metrics_log))
+}
+
+#[derive(Debug)]
+struct BinaryLog {
+    file: Mutex<std::io::BufReader<std::fs::File>>,
+    chunks: Vec<ChunkIndex>,
+    cache: Mutex<VecDeque<(usize, Arc<Vec<Metrics>>)>>,
+}
+
+#[derive(Debug)]
+struct ChunkIndex {
+    start: Duration,
+    end: Duration,
+    offset: u64,
+    len: usize,
+}
+
+impl BinaryLog {
+    fn open(file: std::fs::File) -> anyhow::Result<(Header, Self)> {
+        let file_len = file.metadata()?.len();
+        let mut file = std::io::BufReader::new(file);
+
+        let mut magic = [0; MAGIC.len() + 1];
+        file.read_exact(&mut magic)?;
+        let version = magic[MAGIC.len()];
+        anyhow::ensure!(
+            version == VERSION,
+            "unsupported record file version: {version}"
+        );
+        let header_len = read_varint_from(&mut file)?;
+        anyhow::ensure!(
+            matches!(file.stream_position()?.checked_add(header_len), Some(end) if end <= file_len),
+            "truncated header"
+        );
+        let mut header_bytes = vec![0; header_len as usize];
+        file.read_exact(&mut header_bytes)?;
+        let header = serde_json::from_slice(&header_bytes)
+            .context("failed to parse the header of the record file")?;
+
+        let mut chunks = Vec::new();
+        loop {
+            let position = file.stream_position()?;
+            if position == file_len {
+                break;
+            }
+            let index = (|| {
+                let start = read_varint_from(&mut file)?;
+                let span = read_varint_from(&mut file)?;
+                let _samples = read_varint_from(&mut file)?;
+                let len = read_varint_from(&mut file)?;
+                let offset = file.stream_position()?;
+                anyhow::ensure!(
+                    matches!(offset.checked_add(len), Some(end) if end <= file_len),
+                    "truncated chunk"
+                );
+                let end = start
+                    .checked_add(span)
+                    .ok_or_else(|| anyhow::anyhow!("too large chunk span"))?;
+                file.seek_relative(len as i64)?;
+                Ok(ChunkIndex {
+                    start: Duration::from_micros(start),
+                    end: Duration::from_micros(end),
+                    offset,
+                    len: len as usize,
+                })
+            })();
+            match index {
+                Ok(index) => chunks.push(index),
+                Err(e) => {
+                    // The recording process may have been killed while writing the last chunk.
+                    log::warn!("ignored the broken chunk at offset {position}: {e}");
+                    break;
+                }
+            }
+        }
+
+        Ok((
+            header,
+            Self {
+                file: Mutex::new(file),
+                chunks,
+                cache: Mutex::new(VecDeque::new()),
+            },
+        ))
+    }
+
+    fn read_chunk(&self, i: usize) -> anyhow::Result<Arc<Vec<Metrics>>> {
+        let mut cache = self.cache.lock().expect("unreachable");
+        if let Some((_, samples)) = cache.iter().find(|(j, _)| *j == i) {
+            return Ok(samples.clone());
+        }
+
+        let chunk = &self.chunks[i];
+        let mut body = vec![0; chunk.len];
+        {
+            let mut file = self.file.lock().expect("unreachable");
+            file.seek(SeekFrom::Start(chunk.offset))?;
+            file.read_exact(&mut body)?;
+        }
+        let samples =
+            Arc::new(decode_chunk_body(&body, chunk.start).with_context(|| {
+                format!("failed to decode the chunk at offset {}", chunk.offset)
+            })?);
+
+        if cache.len() >= CHUNK_CACHE_SIZE {
+            cache.pop_front();
+        }
+        cache.push_back((i, samples.clone()));
+        Ok(samples)
+    }
+}
+
+#[derive(Debug, Default)]
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, u64>,
+}
+
+impl StringTable {
+    fn index(&mut self, s: &str) -> u64 {
+        if let Some(&i) = self.indices.get(s) {
+            return i;
+        }
+        let i = self.strings.len() as u64;
+        self.strings.push(s.to_owned());
+        self.indices.insert(s.to_owned(), i);
+        i
+    }
+}
+
+/// Keys of the previous values used for delta encoding: (node index, name index, is counter rate).
+type PrevKey = (Option<u64>, u64, bool);
+
+/// Encodes samples into a chunk incrementally, so that the size of the chunk is known without re-encoding it.
+#[derive(Debug, Default)]
+struct ChunkEncoder {
+    strings: StringTable,
+    strings_bytes: u64,
+    prev_values: HashMap<PrevKey, u64>,
+    sample_count: usize,
+
+    // The timestamps are delta-encoded from the minimum one, which is unknown until the chunk is finished.
+    // So the delta of the first sample is written by `finish()` and `samples_bytes` starts with its flags.
+    first_timestamp: Duration,
+    min: Duration,
+    max: Duration,
+    prev_timestamp: i64,
+    samples_bytes: Vec<u8>,
+}
+
+impl ChunkEncoder {
+    fn is_empty(&self) -> bool {
+        self.sample_count == 0
+    }
+
+    fn string_index(&mut self, s: &str) -> u64 {
+        let count = self.strings.strings.len();
+        let i = self.strings.index(s);
+        if self.strings.strings.len() > count {
+            self.strings_bytes += varint_len(s.len() as u64) + s.len() as u64;
+        }
+        i
+    }
+
+    fn push(&mut self, metrics: &Metrics) {
+        let timestamp = metrics.timestamp.as_micros() as i64;
+        if self.is_empty() {
+            self.first_timestamp = metrics.timestamp;
+            self.min = metrics.timestamp;
+            self.max = metrics.timestamp;
+        } else {
+            write_varint(
+                &mut self.samples_bytes,
+                zigzag(timestamp - self.prev_timestamp),
+            );
+            self.min = self.min.min(metrics.timestamp);
+            self.max = self.max.max(metrics.timestamp);
+        }
+        self.prev_timestamp = timestamp;
+        self.sample_count += 1;
+
+        let node = metrics.node.as_ref().map(|node| self.string_index(node));
+        let mut flags = 0;
+        if metrics.disconnected {
+            flags |= SAMPLE_DISCONNECTED;
+        }
+        if node.is_some() {
+            flags |= SAMPLE_HAS_NODE;
+        }
+        self.samples_bytes.push(flags);
+        if let Some(node) = node {
+            write_varint(&mut self.samples_bytes, node);
+        }
+
+        write_varint(&mut self.samples_bytes, metrics.items.len() as u64);
+        for (name, value) in &metrics.items {
+            let name = self.string_index(name);
+            let (kind, parent) = match value {
+                MetricValue::Gauge { parent, .. } => (KIND_GAUGE, parent),
+                MetricValue::Counter { parent, .. } => (KIND_COUNTER, parent),
+                MetricValue::Utilization { parent, .. } => (KIND_UTILIZATION, parent),
+            };
+            let mut tag = kind;
+            if parent.is_some() {
+                tag |= FLAG_HAS_PARENT;
+            }
+            if let MetricValue::Counter { value: Some(_), .. } = value {
+                tag |= FLAG_HAS_VALUE;
+            }
+
+            write_varint(&mut self.samples_bytes, name);
+            self.samples_bytes.push(tag);
+            if let Some(parent) = parent {
+                let parent = self.string_index(parent);
+                write_varint(&mut self.samples_bytes, parent);
+            }
+
+            let prev_values = &mut self.prev_values;
+            let samples_bytes = &mut self.samples_bytes;
+            let mut encode_int = |v: u64, is_rate: bool| {
+                let prev = prev_values.insert((node, name, is_rate), v).unwrap_or(0);
+                write_varint(samples_bytes, zigzag(v.wrapping_sub(prev) as i64));
+            };
+            match value {
+                MetricValue::Gauge { value, .. } => encode_int(*value, false),
+                MetricValue::Counter {
+                    raw_value, value, ..
+                } => {
+                    encode_int(*raw_value, false);
+                    if let Some(value) = value {
+                        let prev = prev_values
+                            .insert((node, name, true), value.to_bits())
+                            .unwrap_or(0);
+                        write_varint(samples_bytes, value.to_bits() ^ prev);
+                    }
+                }
+                MetricValue::Utilization { value, .. } => {
+                    let prev = prev_values
+                        .insert((node, name, false), value.to_bits())
+                        .unwrap_or(0);
+                    write_varint(samples_bytes, value.to_bits() ^ prev);
+                }
+            }
+        }
+    }
+
+    fn first_timestamp_delta(&self) -> u64 {
+        zigzag((self.first_timestamp - self.min).as_micros() as i64)
+    }
+
+    fn body_len(&self) -> u64 {
+        varint_len(self.strings.strings.len() as u64)
+            + self.strings_bytes
+            + varint_len(self.first_timestamp_delta())
+            + self.samples_bytes.len() as u64
+    }
+
+    /// Returns the size of the encoded chunk in bytes (i.e., the length of the result of [`ChunkEncoder::finish()`]).
+    fn len(&self) -> u64 {
+        let body_len = self.body_len();
+        varint_len(self.min.as_micros() as u64)
+            + varint_len((self.max - self.min).as_micros() as u64)
+            + varint_len(self.sample_count as u64)
+            + varint_len(body_len)
+            + body_len
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut body = Vec::new();
+        write_varint(&mut body, self.strings.strings.len() as u64);
+        for s in &self.strings.strings {
+            write_varint(&mut body, s.len() as u64);
+            body.extend_from_slice(s.as_bytes());
+        }
+        write_varint(&mut body, self.first_timestamp_delta());
+        body.extend_from_slice(&self.samples_bytes);
+
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, self.min.as_micros() as u64);
+        write_varint(&mut bytes, (self.max - self.min).as_micros() as u64);
+        write_varint(&mut bytes, self.sample_count as u64);
+        write_varint(&mut bytes, body.len() as u64);
+        bytes.extend_from_slice(&body);
+        bytes
+    }
+}
+
+fn decode_chunk_body(body: &[u8], start: Duration) -> anyhow::Result<Vec<Metrics>> {
+    let mut reader = ByteReader { bytes: body };
+
+    let string_count = reader.read_varint()?;
+    // Each string takes at least one byte (its length), which bounds the count in a broken chunk.
+    anyhow::ensure!(
+        string_count <= reader.bytes.len() as u64,
+        "too many strings: {string_count}"
+    );
+    let mut strings = Vec::with_capacity(string_count as usize);
+    for _ in 0..string_count {
+        let len = reader.read_varint()? as usize;
+        strings.push(String::from_utf8(reader.read_bytes(len)?.to_vec())?);
+    }
+    let string = |i: u64| {
+        strings
+            .get(i as usize)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("string index out of range: {i}"))
+    };
+
+    let mut prev_values = HashMap::<PrevKey, u64>::new();
+    let mut timestamp = start.as_micros() as i64;
+    let mut samples = Vec::new();
+    while !reader.bytes.is_empty() {
+        timestamp = timestamp
+            .checked_add(unzigzag(reader.read_varint()?))
+            .filter(|t| *t >= 0)
+            .ok_or_else(|| anyhow::anyhow!("timestamp out of range"))?;
+        let flags = reader.read_u8()?;
+        let node = if flags & SAMPLE_HAS_NODE != 0 {
+            Some(reader.read_varint()?)
+        } else {
+            None
+        };
+
+        let mut items = BTreeMap::new();
+        for _ in 0..reader.read_varint()? {
+            let name = reader.read_varint()?;
+            let tag = reader.read_u8()?;
+            let parent = if tag & FLAG_HAS_PARENT != 0 {
+                Some(string(reader.read_varint()?)?)
+            } else {
+                None
+            };
+
+            let mut decode_int = |reader: &mut ByteReader, is_rate: bool| -> anyhow::Result<u64> {
+                let prev = prev_values
+                    .get(&(node, name, is_rate))
+                    .copied()
+                    .unwrap_or(0);
+                let v = prev.wrapping_add(unzigzag(reader.read_varint()?) as u64);
+                prev_values.insert((node, name, is_rate), v);
+                Ok(v)
+            };
+            let value = match tag & 0b11 {
+                KIND_GAUGE => MetricValue::Gauge {
+                    value: decode_int(&mut reader, false)?,
+                    parent,
+                },
+                KIND_COUNTER => {
+                    let raw_value = decode_int(&mut reader, false)?;
+                    let value = if tag & FLAG_HAS_VALUE != 0 {
+                        let prev = prev_values.get(&(node, name, true)).copied().unwrap_or(0);
+                        let bits = reader.read_varint()? ^ prev;
+                        prev_values.insert((node, name, true), bits);
+                        Some(f64::from_bits(bits))
+                    } else {
+                        None
+                    };
+                    MetricValue::Counter {
+                        raw_value,
+                        value,
+                        parent,
+                    }
+                }
+                KIND_UTILIZATION => {
+                    let prev = prev_values.get(&(node, name, false)).copied().unwrap_or(0);
+                    let bits = reader.read_varint()? ^ prev;
+                    prev_values.insert((node, name, false), bits);
+                    MetricValue::Utilization {
+                        value: f64::from_bits(bits),
+                        parent,
+                    }
+                }
+                kind => anyhow::bail!("unknown metric kind: {kind}"),
+            };
+            items.insert(string(name)?, value);
+        }
+
+        samples.push(Metrics {
+            items,
+            node: node.map(&string).transpose()?,
+            disconnected: flags & SAMPLE_DISCONNECTED != 0,
+            ..Metrics::with_timestamp(Duration::from_micros(timestamp as u64))
+        });
+    }
+    Ok(samples)
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn read_u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_bytes(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        anyhow::ensure!(n <= self.bytes.len(), "unexpected end of chunk");
+        let (bytes, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn read_varint(&mut self) -> anyhow::Result<u64> {
+        read_varint_from(&mut self.bytes)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn varint_len(n: u64) -> u64 {
+    let mut bytes = Vec::new();
+    write_varint(&mut bytes, n);
+    bytes.len() as u64
+}
+
+fn read_varint_from(reader: &mut impl Read) -> anyhow::Result<u64> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut b = [0];
+        reader.read_exact(&mut b)?;
+        n |= u64::from(b[0] & 0x7F) << shift;
+        if b[0] & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    anyhow::bail!("too long varint")
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("erldash-{}-{name}", std::process::id()))
+    }
+
+    fn header() -> Header {
+        let node = |name: &str| {
+            serde_json::json!({
+                "system_version": "Erlang/OTP 26",
+                "node_name": name,
+                "start_time": "2024-01-01T00:00:00+00:00",
+            })
+        };
+        let mut header = node("a@localhost");
+        header["cluster_nodes"] = serde_json::json!([node("a@localhost"), node("b@localhost")]);
+        serde_json::from_value(header).expect("unreachable")
+    }
+
+    fn samples() -> Vec<Metrics> {
+        let mut samples = Vec::new();
+        for i in 0..(CHUNK_SAMPLES as u64 * 2 + 3) {
+            let node = if i % 2 == 0 {
+                "a@localhost"
+            } else {
+                "b@localhost"
+            };
+            let mut metrics = Metrics::with_timestamp(Duration::from_millis(i * 500));
+            metrics.node = Some(node.to_owned());
+            if i % 10 == 9 {
+                metrics.disconnected = true;
+                samples.push(metrics);
+                continue;
+            }
+            metrics.insert("memory.total_bytes", MetricValue::gauge(1000 + i * 7));
+            metrics.insert(
+                "memory.ets_bytes",
+                MetricValue::gauge_with_parent(100 - i % 5, "memory.total_bytes"),
+            );
+            metrics.insert(
+                "statistics.reductions",
+                MetricValue::Counter {
+                    raw_value: u64::MAX - i,
+                    value: (i > 0).then_some(i as f64 * 0.5),
+                    parent: None,
+                },
+            );
+            metrics.insert(
+                "statistics.context_switches",
+                MetricValue::counter_with_parent(i * 3, "statistics.reductions"),
+            );
+            metrics.insert(
+                "utilization.scheduler",
+                MetricValue::utilization(i as f64 / 3.0),
+            );
+            metrics.insert(
+                "utilization.scheduler.thread.1",
+                MetricValue::utilization_with_parent(-0.0, "utilization.scheduler"),
+            );
+            samples.push(metrics);
+        }
+        samples
+    }
+
+    fn assert_same_metrics(expected: &[Metrics], actual: &[Metrics]) {
+        assert_eq!(expected.len(), actual.len());
+        for (e, a) in expected.iter().zip(actual) {
+            assert_eq!(e.timestamp, a.timestamp);
+            assert_eq!(e.node, a.node);
+            assert_eq!(e.disconnected, a.disconnected);
+            assert_eq!(
+                serde_json::to_string(&e.items).expect("unreachable"),
+                serde_json::to_string(&a.items).expect("unreachable")
+            );
+        }
+    }
+
+    fn round_trip(format: RecordFormat, name: &str) {
+        let path = temp_path(name);
+        let samples = samples();
+        smol::block_on(async {
+            let recorder = Recorder::create(&path, format).expect("unreachable");
+            recorder.write_header(&header()).await.expect("unreachable");
+            for metrics in &samples {
+                recorder.write_metrics(metrics).await.expect("unreachable");
+            }
+            recorder.flush().await.expect("unreachable");
+            assert_eq!(
+                recorder.size().await,
+                std::fs::metadata(&path).expect("unreachable").len()
+            );
+        });
+
+        let reader = RecordReader::open(&path).expect("unreachable");
+        let node_names = reader
+            .header
+            .nodes()
+            .iter()
+            .map(|node| node.node_name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(node_names, ["a@localhost", "b@localhost"]);
+        assert_eq!(
+            reader.last_time(),
+            samples.last().expect("unreachable").timestamp
+        );
+        assert_same_metrics(&samples, &reader.read_all().expect("unreachable"));
+
+        let range = reader
+            .get_range(Duration::from_secs(10), Duration::from_secs(40))
+            .expect("unreachable");
+        assert_same_metrics(&samples[20..=80], &range);
+
+        std::fs::remove_file(&path).expect("unreachable");
+    }
+
+    #[test]
+    fn json_round_trip_works() {
+        round_trip(RecordFormat::Json, "round-trip.jsonl");
+    }
+
+    #[test]
+    fn binary_round_trip_works() {
+        round_trip(RecordFormat::Binary, "round-trip.bin");
+    }
+
+    #[test]
+    fn binary_size_includes_pending_samples() {
+        let path = temp_path("pending.bin");
+        let size = smol::block_on(async {
+            let recorder = Recorder::create(&path, RecordFormat::Binary).expect("unreachable");
+            recorder.write_header(&header()).await.expect("unreachable");
+            let header_size = recorder.size().await;
+            for metrics in &samples()[..2] {
+                recorder.write_metrics(metrics).await.expect("unreachable");
+            }
+            let size = recorder.size().await;
+            assert!(size > header_size);
+            recorder.flush().await.expect("unreachable");
+            size
+        });
+        assert_eq!(std::fs::metadata(&path).expect("unreachable").len(), size);
+        std::fs::remove_file(&path).expect("unreachable");
+    }
+
+    #[test]
+    fn too_large_header_len_is_rejected() {
+        let path = temp_path("broken-header.bin");
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        write_varint(&mut bytes, u64::MAX);
+        std::fs::write(&path, &bytes).expect("unreachable");
+        assert!(RecordReader::open(&path).is_err());
+        std::fs::remove_file(&path).expect("unreachable");
+    }
+
+    #[test]
+    fn corrupt_chunk_is_rejected() {
+        let mut body = Vec::new();
+        write_varint(&mut body, u64::MAX);
+        assert!(decode_chunk_body(&body, Duration::ZERO).is_err());
+
+        let mut body = Vec::new();
+        write_varint(&mut body, 0);
+        write_varint(&mut body, zigzag(i64::MAX));
+        body.push(0);
+        write_varint(&mut body, 0);
+        write_varint(&mut body, zigzag(i64::MAX));
+        body.push(0);
+        write_varint(&mut body, 0);
+        assert!(decode_chunk_body(&body, Duration::from_micros(1)).is_err());
+
+        let path = temp_path("corrupt-chunk.bin");
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        let json = serde_json::to_vec(&header()).expect("unreachable");
+        write_varint(&mut bytes, json.len() as u64);
+        bytes.extend_from_slice(&json);
+        let mut body = Vec::new();
+        write_varint(&mut body, u64::MAX >> 1);
+        body.extend_from_slice(&[0xFF; 8]);
+        write_varint(&mut bytes, 0);
+        write_varint(&mut bytes, 0);
+        write_varint(&mut bytes, 1);
+        write_varint(&mut bytes, body.len() as u64);
+        bytes.extend_from_slice(&body);
+        std::fs::write(&path, &bytes).expect("unreachable");
+        let reader = RecordReader::open(&path).expect("unreachable");
+        assert!(reader.read_all().is_err());
+        std::fs::remove_file(&path).expect("unreachable");
+    }
+
+    #[test]
+    fn too_large_chunk_span_is_ignored() {
+        let path = temp_path("broken-chunk.bin");
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        let json = serde_json::to_vec(&header()).expect("unreachable");
+        write_varint(&mut bytes, json.len() as u64);
+        bytes.extend_from_slice(&json);
+        write_varint(&mut bytes, u64::MAX);
+        write_varint(&mut bytes, u64::MAX);
+        write_varint(&mut bytes, 0);
+        write_varint(&mut bytes, 0);
+        std::fs::write(&path, &bytes).expect("unreachable");
+        let reader = RecordReader::open(&path).expect("unreachable");
+        assert!(reader.read_all().expect("unreachable").is_empty());
+        std::fs::remove_file(&path).expect("unreachable");
+    }
+}
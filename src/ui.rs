@@ -1,11 +1,18 @@
-use crate::metrics::{format_u64, Header, MetricValue, Metrics, MetricsPoller};
+use crate::alert::Alert;
+use crate::erlang::ProcessDetail;
+use crate::metrics::{
+    format_u64, AvgValue, CollectorError, EtsTableMetrics, Header, MetricValue, Metrics,
+    MetricsPoller, PortMetrics, ProcessMetrics, Subscription,
+};
+use crate::ChartWindow;
 use crossterm::event::{KeyCode, KeyEvent};
+use erl_dist::term::Pid;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use ratatui::style::{Modifier, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::symbols::Marker;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
-    Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table, TableState,
+    Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table, TableState, Tabs,
 };
 use ratatui::Frame;
 use std::collections::{BTreeMap, VecDeque};
@@ -14,28 +21,40 @@ use std::time::{Duration, Instant};
 
 type Terminal = ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>;
 
-const ONE_MINUTE: u64 = 60;
-const CHART_DURATION: u64 = ONE_MINUTE;
 const POLL_TIMEOUT: Duration = Duration::from_millis(10);
+const PROCESS_TOP_N: usize = 100;
+const ALERTS_PANE_MAX_ROWS: usize = 5;
+const ERRORS_PANE_MAX_ROWS: usize = 3;
+
+// Braille markers have two dots per cell horizontally.
+const CHART_POINTS_PER_CELL: usize = 2;
 
 pub struct App {
     terminal: Terminal,
     poller: MetricsPoller,
-    ui: UiState,
+    uis: Vec<UiState>, // one per target node
+    cluster: Option<ClusterState>,
     replay_cursor_time: Duration,
 }
 
 impl App {
-    pub fn new(poller: MetricsPoller) -> anyhow::Result<Self> {
+    pub fn new(poller: MetricsPoller, window: ChartWindow) -> anyhow::Result<Self> {
         let terminal = Self::setup_terminal()?;
         log::debug!("setup terminal");
 
         let replay_mode = poller.is_replay();
         let header = poller.header().clone();
+        let uis = header
+            .nodes()
+            .iter()
+            .map(|node| UiState::new(node.clone(), replay_mode, header.is_cluster(), window))
+            .collect();
+        let cluster = header.is_cluster().then(|| ClusterState::new(header));
         Ok(Self {
             terminal,
             poller,
-            ui: UiState::new(header, replay_mode),
+            uis,
+            cluster,
             replay_cursor_time: Duration::default(),
         })
     }
@@ -46,7 +65,7 @@ impl App {
             if self.handle_event()? {
                 break;
             }
-            if self.ui.pause || self.ui.replay_mode {
+            if self.uis[0].pause || self.uis[0].replay_mode {
                 std::thread::sleep(POLL_TIMEOUT);
             } else {
                 self.handle_poll()?;
@@ -63,35 +82,11 @@ impl App {
             Err(mpsc::RecvTimeoutError::Timeout) => {}
             Ok(metrics) => {
                 log::debug!("recv new metrics");
-
-                for (name, item) in &metrics.items {
-                    if let Some(avg) = self.ui.averages.get_mut(name) {
-                        avg.add(item.clone());
-                    } else {
-                        self.ui
-                            .averages
-                            .insert(name.clone(), AvgValue::new(item.clone()));
-                    }
-                }
-
-                let timestamp = metrics.timestamp;
-                self.ui.history.push_back(metrics);
-                while let Some(metrics) = self.ui.history.pop_front() {
-                    let duration = (timestamp - metrics.timestamp).as_secs();
-                    if duration <= CHART_DURATION {
-                        self.ui.history.push_front(metrics);
-                        break;
-                    }
-                    for (name, item) in metrics.items {
-                        self.ui
-                            .averages
-                            .get_mut(&name)
-                            .expect("unreachable")
-                            .sub(item.clone());
-                    }
-                    log::debug!("remove old metrics");
-                }
-                self.ui.elapsed = self.ui.start.elapsed();
+                let Some(i) = self.poller.header().node_index(&metrics) else {
+                    log::warn!("received metrics of an unknown node: {:?}", metrics.node);
+                    return Ok(());
+                };
+                self.uis[i].push_metrics(metrics);
                 self.render_ui()?;
             }
         }
@@ -121,7 +116,15 @@ impl App {
                 return Ok(true);
             }
             KeyCode::Char('p') => {
-                self.ui.pause = !self.ui.pause;
+                for ui in &mut self.uis {
+                    ui.pause = !ui.pause;
+                }
+            }
+            KeyCode::Char('w') => {
+                for ui in &mut self.uis {
+                    ui.set_window(ui.window.next());
+                }
+                self.render_replay_ui_if_need()?;
             }
             KeyCode::Char('h') => {
                 self.replay_cursor_time = self
@@ -137,79 +140,84 @@ impl App {
                     self.render_replay_ui_if_need()?;
                 }
             }
-            KeyCode::Left => {
-                self.ui.focus = Focus::Main;
-            }
-            KeyCode::Right => {
-                self.ui.focus = Focus::Sub;
-            }
-            KeyCode::Up => {
-                let table = if self.ui.focus == Focus::Main {
-                    &mut self.ui.metrics_table_state
-                } else {
-                    &mut self.ui.detail_table_state
-                };
-
-                let i = table.selected().unwrap_or(0).saturating_sub(1);
-                table.select(Some(i));
-            }
-            KeyCode::Down => {
-                let table = if self.ui.focus == Focus::Main {
-                    &mut self.ui.metrics_table_state
-                } else {
-                    &mut self.ui.detail_table_state
-                };
-
-                let i = table.selected().unwrap_or(0) + 1;
-                table.select(Some(i));
-            }
-            _ => {
-                return Ok(false);
-            }
+            _ => match &mut self.cluster {
+                Some(cluster) if cluster.selected_node.is_none() => {
+                    if !cluster.handle_key_event(key, &self.poller, &self.uis) {
+                        return Ok(false);
+                    }
+                }
+                cluster => {
+                    let i = cluster.as_ref().and_then(|c| c.selected_node).unwrap_or(0);
+                    if !self.uis[i].handle_key_event(key, &self.poller, i) {
+                        if let (Some(cluster), KeyCode::Esc) = (cluster, key.code) {
+                            cluster.selected_node = None;
+                            self.poller.subscribe(i, Subscription::default());
+                        } else {
+                            return Ok(false);
+                        }
+                    }
+                }
+            },
         }
         self.render_ui()?;
         Ok(false)
     }
 
     fn render_ui(&mut self) -> anyhow::Result<()> {
-        if !self.ui.history.is_empty() {
-            self.terminal.draw(|f| self.ui.render(f))?;
+        match &mut self.cluster {
+            Some(cluster) if cluster.selected_node.is_none() => {
+                if self.uis.iter().any(|ui| ui.has_metrics()) {
+                    let uis = &self.uis;
+                    self.terminal.draw(|f| cluster.render(f, uis))?;
+                }
+            }
+            cluster => {
+                let i = cluster.as_ref().and_then(|c| c.selected_node).unwrap_or(0);
+                let ui = &mut self.uis[i];
+                if ui.has_metrics() {
+                    self.terminal.draw(|f| ui.render(f))?;
+                }
+            }
         }
         Ok(())
     }
 
     fn render_replay_ui_if_need(&mut self) -> anyhow::Result<()> {
-        if !self.ui.replay_mode {
+        if !self.uis[0].replay_mode {
             return Ok(());
         }
 
         let time = self.replay_cursor_time;
 
-        self.ui.history.clear();
-        self.ui.averages.clear();
+        for ui in &mut self.uis {
+            ui.history.clear();
+            ui.history_full = false;
+            ui.averages.clear();
+        }
         for metrics in self
             .poller
-            .get_metrics_range(time, time + Duration::from_secs(CHART_DURATION))?
+            .get_metrics_range(time, time + self.uis[0].window.duration())?
         {
-            self.ui.history.push_back(metrics.clone());
+            let Some(i) = self.poller.header().node_index(&metrics) else {
+                continue;
+            };
+            let ui = &mut self.uis[i];
 
             for (name, item) in &metrics.items {
-                if let Some(avg) = self.ui.averages.get_mut(name) {
+                if let Some(avg) = ui.averages.get_mut(name) {
                     avg.add(item.clone());
                 } else {
-                    self.ui
-                        .averages
+                    ui.averages
                         .insert(name.clone(), AvgValue::new(item.clone()));
                 }
             }
+            ui.history.push_back(metrics);
         }
 
-        self.ui.elapsed = self
-            .ui
-            .history
-            .back()
-            .map(|x| x.timestamp)
-            .unwrap_or_default();
+        for ui in &mut self.uis {
+            ui.elapsed = ui.history.back().map(|x| x.timestamp).unwrap_or_default();
+            ui.disconnected = ui.history.back().map_or(false, |x| x.disconnected);
+        }
 
         self.render_ui()?;
         Ok(())
@@ -253,14 +261,38 @@ struct UiState {
     pause: bool,
     history: VecDeque<Metrics>,
     averages: BTreeMap<String, AvgValue>,
+    window: ChartWindow,
+
+    // `true` if older metrics than the window have been evicted from `history`.
+    history_full: bool,
     focus: Focus,
     metrics_table_state: TableState,
     detail_table_state: TableState,
     replay_mode: bool,
+    tab: Tab,
+    processes: Vec<ProcessMetrics>,
+    process_sort_key: ProcessSortKey,
+    process_table_state: TableState,
+    selected_pid: Option<Pid>,
+    process_detail: Option<ProcessDetail>,
+    process_detail_table_state: TableState,
+    ports: Vec<PortMetrics>,
+    port_sort_key: PortSortKey,
+    port_table_state: TableState,
+    ets_tables: Vec<EtsTableMetrics>,
+    ets_sort_key: EtsSortKey,
+    ets_table_state: TableState,
+    cluster_mode: bool,
+    disconnected: bool,
+    alerts: Vec<Alert>,
+    slow_polls: usize,
+    last_slow_poll: Option<Duration>,
+    errors: VecDeque<(Duration, CollectorError)>, // recent collector failures
+    error_count: usize,
 }
 
 impl UiState {
-    fn new(header: Header, replay_mode: bool) -> Self {
+    fn new(header: Header, replay_mode: bool, cluster_mode: bool, window: ChartWindow) -> Self {
         Self {
             start: Instant::now(),
             header,
@@ -268,21 +300,278 @@ impl UiState {
             pause: false,
             history: VecDeque::new(),
             averages: BTreeMap::new(),
+            window,
+            history_full: false,
             focus: Focus::Main,
             metrics_table_state: TableState::default(),
             detail_table_state: TableState::default(),
             replay_mode,
+            tab: Tab::Metrics,
+            processes: Vec::new(),
+            process_sort_key: ProcessSortKey::Reductions,
+            process_table_state: TableState::default(),
+            selected_pid: None,
+            process_detail: None,
+            process_detail_table_state: TableState::default(),
+            ports: Vec::new(),
+            port_sort_key: PortSortKey::TotalBytes,
+            port_table_state: TableState::default(),
+            ets_tables: Vec::new(),
+            ets_sort_key: EtsSortKey::Memory,
+            ets_table_state: TableState::default(),
+            cluster_mode,
+            disconnected: false,
+            alerts: Vec::new(),
+            slow_polls: 0,
+            last_slow_poll: None,
+            errors: VecDeque::new(),
+            error_count: 0,
+        }
+    }
+
+    fn push_metrics(&mut self, mut metrics: Metrics) {
+        self.disconnected = metrics.disconnected;
+        self.processes = std::mem::take(&mut metrics.processes);
+        self.sort_processes();
+        self.process_detail = metrics.process_detail.take();
+        self.ports = std::mem::take(&mut metrics.ports);
+        self.sort_ports();
+        self.ets_tables = std::mem::take(&mut metrics.ets_tables);
+        self.sort_ets_tables();
+
+        self.alerts = std::mem::take(&mut metrics.alerts);
+        if let Some(duration) = metrics.slow_poll {
+            self.slow_polls += 1;
+            self.last_slow_poll = Some(duration);
+        }
+        for error in &metrics.errors {
+            self.error_count += 1;
+            self.errors.push_back((metrics.timestamp, error.clone()));
+            if self.errors.len() > ERRORS_PANE_MAX_ROWS {
+                self.errors.pop_front();
+            }
+        }
+
+        for (name, item) in &metrics.items {
+            if let Some(avg) = self.averages.get_mut(name) {
+                avg.add(item.clone());
+            } else {
+                self.averages
+                    .insert(name.clone(), AvgValue::new(item.clone()));
+            }
+        }
+
+        self.history.push_back(metrics);
+        self.evict_old_metrics();
+        self.elapsed = self.start.elapsed();
+    }
+
+    fn set_window(&mut self, window: ChartWindow) {
+        if window.duration() > self.window.duration() {
+            self.history_full = false;
         }
+        self.window = window;
+        self.evict_old_metrics();
+    }
+
+    fn evict_old_metrics(&mut self) {
+        let Some(timestamp) = self.history.back().map(|m| m.timestamp) else {
+            return;
+        };
+        while let Some(metrics) = self.history.pop_front() {
+            let duration = timestamp - metrics.timestamp;
+            if duration <= self.window.duration() {
+                self.history.push_front(metrics);
+                break;
+            }
+            for (name, item) in metrics.items {
+                self.averages
+                    .get_mut(&name)
+                    .expect("unreachable")
+                    .sub(item.clone());
+            }
+            self.history_full = true;
+            log::debug!("remove old metrics");
+        }
+    }
+
+    fn is_avg_available(&self) -> bool {
+        if self.history_full {
+            return true;
+        }
+        match (self.history.front(), self.history.back()) {
+            (Some(first), Some(last)) => {
+                (last.timestamp - first.timestamp).as_secs() + 1 >= self.window.duration().as_secs()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `false` if the key is not handled.
+    fn handle_key_event(
+        &mut self,
+        key: KeyEvent,
+        poller: &MetricsPoller,
+        node_index: usize,
+    ) -> bool {
+        match key.code {
+            KeyCode::Tab => {
+                self.tab = self.tab.next();
+                self.focus = Focus::Main;
+                self.selected_pid = None;
+                poller.subscribe(node_index, self.subscription());
+            }
+            KeyCode::Char('s') if self.tab == Tab::Processes => {
+                self.process_sort_key = self.process_sort_key.next();
+                self.sort_processes();
+            }
+            KeyCode::Char('s') if self.tab == Tab::Ports => {
+                self.port_sort_key = self.port_sort_key.next();
+                self.sort_ports();
+            }
+            KeyCode::Char('s') if self.tab == Tab::Ets => {
+                self.ets_sort_key = self.ets_sort_key.next();
+                self.sort_ets_tables();
+            }
+            KeyCode::Enter if self.tab == Tab::Processes => {
+                let i = self.process_table_state.selected().unwrap_or(0);
+                if let Some(process) = self.processes.get(i) {
+                    self.selected_pid = Some(process.info.pid.clone());
+                    self.process_detail = None;
+                    self.process_detail_table_state = TableState::default();
+                    self.focus = Focus::ProcessDetail;
+                    poller.subscribe(node_index, self.subscription());
+                }
+            }
+            KeyCode::Esc if self.tab == Tab::Processes && self.selected_pid.is_some() => {
+                self.selected_pid = None;
+                self.process_detail = None;
+                self.focus = Focus::Main;
+                poller.subscribe(node_index, self.subscription());
+            }
+            KeyCode::Left => {
+                self.focus = Focus::Main;
+            }
+            KeyCode::Right if self.tab == Tab::Processes => {
+                if self.selected_pid.is_some() {
+                    self.focus = Focus::ProcessDetail;
+                }
+            }
+            KeyCode::Right => {
+                self.focus = Focus::Sub;
+            }
+            KeyCode::Up => {
+                let table = self.focused_table_state();
+                let i = table.selected().unwrap_or(0).saturating_sub(1);
+                table.select(Some(i));
+            }
+            KeyCode::Down => {
+                let table = self.focused_table_state();
+                let i = table.selected().unwrap_or(0) + 1;
+                table.select(Some(i));
+            }
+            _ => {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn focused_table_state(&mut self) -> &mut TableState {
+        if self.focus == Focus::ProcessDetail {
+            &mut self.process_detail_table_state
+        } else if self.tab == Tab::Processes {
+            &mut self.process_table_state
+        } else if self.tab == Tab::Ports {
+            &mut self.port_table_state
+        } else if self.tab == Tab::Ets {
+            &mut self.ets_table_state
+        } else if self.focus == Focus::Main {
+            &mut self.metrics_table_state
+        } else {
+            &mut self.detail_table_state
+        }
+    }
+
+    fn subscription(&self) -> Subscription {
+        let processes = self.tab == Tab::Processes;
+        Subscription {
+            processes,
+            process_detail: self.selected_pid.clone().filter(|_| processes),
+            ports: self.tab == Tab::Ports,
+            ets_tables: self.tab == Tab::Ets,
+        }
+    }
+
+    fn sort_ports(&mut self) {
+        let sort_key = self.port_sort_key;
+        self.ports.sort_by(|a, b| {
+            sort_key
+                .sort_value(b)
+                .total_cmp(&sort_key.sort_value(a))
+                .then_with(|| a.info.id.cmp(&b.info.id))
+        });
+    }
+
+    fn sort_processes(&mut self) {
+        let sort_key = self.process_sort_key;
+        self.processes.sort_by(|a, b| {
+            sort_key
+                .sort_value(b)
+                .total_cmp(&sort_key.sort_value(a))
+                .then_with(|| a.name().cmp(b.name()))
+        });
+    }
+
+    fn sort_ets_tables(&mut self) {
+        let sort_key = self.ets_sort_key;
+        self.ets_tables.sort_by(|a, b| {
+            sort_key
+                .sort_value(b)
+                .total_cmp(&sort_key.sort_value(a))
+                .then_with(|| a.info.id.cmp(&b.info.id))
+        });
     }
 
     fn render(&mut self, f: &mut Frame) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
             .split(f.size());
 
         self.render_header(f, chunks[0]);
-        self.render_body(f, chunks[1]);
+        self.render_tabs(f, chunks[1]);
+        match self.tab {
+            Tab::Metrics => self.render_body(f, chunks[2]),
+            Tab::Processes => self.render_processes_body(f, chunks[2]),
+            Tab::Ports => self.render_ports_body(f, chunks[2]),
+            Tab::Ets => self.render_ets_body(f, chunks[2]),
+        }
+    }
+
+    fn render_tabs(&mut self, f: &mut Frame, area: Rect) {
+        let titles = Tab::ALL.iter().map(|tab| tab.title()).collect::<Vec<_>>();
+        let index = Tab::ALL
+            .iter()
+            .position(|tab| *tab == self.tab)
+            .expect("unreachable");
+        let tabs = Tabs::new(titles)
+            .select(index)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_widget(tabs, area);
+
+        if self.cluster_mode {
+            let paragraph = Paragraph::new(vec![Line::from("Cluster summary: ESC key ")])
+                .alignment(Alignment::Right);
+            f.render_widget(paragraph, area);
+        }
     }
 
     fn render_header(&mut self, f: &mut Frame, area: Rect) {
@@ -291,30 +580,70 @@ impl UiState {
             .constraints(
                 [
                     Constraint::Percentage(20),
-                    Constraint::Percentage(60),
+                    Constraint::Percentage(45),
+                    Constraint::Percentage(15),
                     Constraint::Percentage(20),
                 ]
                 .as_ref(),
             )
             .split(area);
 
-        let paragraph = Paragraph::new(vec![Line::from(self.header.node_name.clone())])
-            .block(self.make_block("Node"))
+        let node = if self.disconnected {
+            Line::from(vec![
+                Span::from(format!("{} ", self.header.node_name)),
+                Span::styled("DISCONNECTED", disconnected_style()),
+            ])
+        } else {
+            Line::from(self.header.node_name.clone())
+        };
+        let paragraph = Paragraph::new(vec![node])
+            .block(make_block("Node"))
             .alignment(Alignment::Left);
         f.render_widget(paragraph, chunks[0]);
 
         let paragraph = Paragraph::new(vec![Line::from(self.header.system_version.get())])
-            .block(self.make_block("System Version"))
+            .block(make_block("System Version"))
             .alignment(Alignment::Left);
         f.render_widget(paragraph, chunks[1]);
 
+        let mut interval = self
+            .header
+            .polling_interval
+            .map(|d| format!("{d:?}"))
+            .unwrap_or_default();
+        let rpc_latency = self
+            .history
+            .iter()
+            .rev()
+            .find(|m| !m.disconnected)
+            .and_then(|m| m.items.get("poll.rpc_latency_us"))
+            .and_then(|v| v.as_f64());
+        if let Some(us) = rpc_latency {
+            interval.push_str(&format!(" (RPC {:.1?})", Duration::from_micros(us as u64)));
+        }
+        let polling = if let Some(last) = self.last_slow_poll {
+            Line::from(vec![
+                Span::from(format!("{interval} ")),
+                Span::styled(
+                    format!("SLOW x{} ({last:.1?})", self.slow_polls),
+                    Style::default().fg(Color::Yellow),
+                ),
+            ])
+        } else {
+            Line::from(interval)
+        };
+        let paragraph = Paragraph::new(vec![polling])
+            .block(make_block("Polling"))
+            .alignment(Alignment::Left);
+        f.render_widget(paragraph, chunks[2]);
+
         let now = self.header.start_time + self.elapsed;
         let paragraph = Paragraph::new(vec![Line::from(
             now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
         )])
-        .block(self.make_block("Time"))
+        .block(make_block("Time"))
         .alignment(Alignment::Left);
-        f.render_widget(paragraph, chunks[2]);
+        f.render_widget(paragraph, chunks[3]);
     }
 
     fn render_body(&mut self, f: &mut Frame, area: Rect) {
@@ -328,35 +657,484 @@ impl UiState {
     }
 
     fn render_body_left(&mut self, f: &mut Frame, area: Rect) {
+        let alerts_height = if self.alerts.is_empty() {
+            0
+        } else {
+            std::cmp::min(self.alerts.len(), ALERTS_PANE_MAX_ROWS) as u16 + 3
+        };
+        let errors_height = if self.errors.is_empty() {
+            0
+        } else {
+            self.errors.len() as u16 + 2
+        };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(0), Constraint::Length(5)].as_ref())
+            .constraints(
+                [
+                    Constraint::Min(0),
+                    Constraint::Length(alerts_height),
+                    Constraint::Length(errors_height),
+                    Constraint::Length(6),
+                ]
+                .as_ref(),
+            )
             .split(area);
         self.render_metrics(f, chunks[0]);
+        if !self.alerts.is_empty() {
+            self.render_alerts(f, chunks[1]);
+        }
+        if !self.errors.is_empty() {
+            self.render_errors(f, chunks[2]);
+        }
+        self.render_help(f, chunks[3]);
+    }
+
+    fn render_errors(&mut self, f: &mut Frame, area: Rect) {
+        let block = make_block(&format!("Collector Errors ({})", self.error_count));
+        let rows = self.errors.iter().rev().map(|(timestamp, error)| {
+            let time = self.header.start_time
+                + chrono::Duration::from_std(*timestamp)
+                    .unwrap_or_else(|_| chrono::Duration::zero());
+            Row::new(vec![
+                Cell::from(time.format("%H:%M:%S").to_string()),
+                Cell::from(error.collector.clone()),
+                Cell::from(error.message.clone()),
+            ])
+            .style(Style::default().fg(Color::Yellow))
+        });
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Percentage(20),
+            Constraint::Percentage(80),
+        ];
+        let table = Table::new(rows, widths).block(block);
+        f.render_widget(table, area);
+    }
+
+    fn render_alerts(&mut self, f: &mut Frame, area: Rect) {
+        let block = make_block(&format!("Alerts ({})", self.alerts.len()));
+
+        let header_cells = ["Rule", "Metric", "Value", "Since"]
+            .into_iter()
+            .map(|h| Cell::from(h).style(Style::default().add_modifier(Modifier::BOLD)));
+        let header = Row::new(header_cells);
+
+        let rows = self.alerts.iter().map(|alert| {
+            let value = if alert.value.fract() == 0.0 && alert.value >= 0.0 {
+                format_u64(alert.value as u64, "")
+            } else {
+                format!("{:.1}", alert.value)
+            };
+            Row::new(vec![
+                Cell::from(alert.rule.clone()),
+                Cell::from(alert.metric.clone()),
+                Cell::from(value),
+                Cell::from(alert.start_time.format("%H:%M:%S").to_string()),
+            ])
+            .style(alert_style())
+        });
+
+        let widths = [
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+        ];
+        let table = Table::new(rows, widths).header(header).block(block);
+        f.render_widget(table, area);
+    }
+
+    /// Returns `true` if an alert is firing for the metric (or one of its children if `include_children` is `true`).
+    fn is_alerting(&self, name: &str, include_children: bool) -> bool {
+        self.alerts.iter().any(|alert| {
+            alert.metric == name
+                || (include_children
+                    && self
+                        .latest_metrics()
+                        .child_items(name)
+                        .any(|(child, _)| alert.metric == child))
+        })
+    }
+
+    fn render_processes_body(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(6)].as_ref())
+            .split(area);
+        if self.selected_pid.is_some() {
+            let upper_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(chunks[0]);
+            self.render_processes(f, upper_chunks[0]);
+            self.render_process_detail(f, upper_chunks[1]);
+        } else {
+            self.render_processes(f, chunks[0]);
+        }
+        self.render_help(f, chunks[1]);
+    }
+
+    fn render_ports_body(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(6)].as_ref())
+            .split(area);
+        self.render_ports(f, chunks[0]);
         self.render_help(f, chunks[1]);
     }
 
+    fn render_ports(&mut self, f: &mut Frame, area: Rect) {
+        if self.replay_mode {
+            let paragraph = Paragraph::new(vec![Line::from(
+                "The port list is not available in replay mode.",
+            )])
+            .block(make_block("Ports (REPLAY)"))
+            .alignment(Alignment::Left);
+            f.render_widget(paragraph, area);
+            return;
+        }
+
+        let block = if self.pause {
+            make_block("Ports (PAUSED)")
+        } else {
+            make_block("Ports")
+        };
+
+        let header_cells = PortSortKey::COLUMNS.iter().map(|(title, key)| {
+            // `TotalBytes` is the sum of the "Input" and "Output" columns.
+            let is_sorted = *key == Some(self.port_sort_key)
+                || (self.port_sort_key == PortSortKey::TotalBytes
+                    && matches!(
+                        key,
+                        Some(PortSortKey::InputBytes | PortSortKey::OutputBytes)
+                    ));
+            let title = if is_sorted {
+                format!("{title} ▼")
+            } else {
+                title.to_string()
+            };
+            Cell::from(title).style(Style::default().add_modifier(Modifier::BOLD))
+        });
+        let header = Row::new(header_cells).bottom_margin(1);
+
+        let format_rate = |v: Option<f64>| v.map(|v| format_u64(v.round() as u64, "/s"));
+        let format_stat = |v: Option<u64>| v.map(|v| format_u64(v, ""));
+        let rows = self
+            .ports
+            .iter()
+            .map(|p| {
+                Row::new(vec![
+                    Cell::from(p.info.id.clone()),
+                    Cell::from(p.name().to_owned()),
+                    Cell::from(p.info.connected.clone()),
+                    Cell::from(p.info.os_pid.map(|x| x.to_string()).unwrap_or_default()),
+                    Cell::from(format!(
+                        "{:>14}",
+                        format_rate(p.input_bytes).unwrap_or_default()
+                    )),
+                    Cell::from(format!(
+                        "{:>14}",
+                        format_rate(p.output_bytes).unwrap_or_default()
+                    )),
+                    Cell::from(format!("{:>12}", format_u64(p.info.queue_size, ""))),
+                    Cell::from(format!(
+                        "{:>12}",
+                        format_stat(p.socket_stat("recv_cnt")).unwrap_or_default()
+                    )),
+                    Cell::from(format!(
+                        "{:>12}",
+                        format_stat(p.socket_stat("send_cnt")).unwrap_or_default()
+                    )),
+                    Cell::from(format!(
+                        "{:>12}",
+                        format_stat(p.socket_stat("send_pend")).unwrap_or_default()
+                    )),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Percentage(20),
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Length(16),
+            Constraint::Length(16),
+            Constraint::Length(14),
+            Constraint::Length(14),
+            Constraint::Length(14),
+            Constraint::Length(14),
+        ];
+        let selected = std::cmp::min(
+            self.port_table_state.selected().unwrap_or(0),
+            rows.len().saturating_sub(1),
+        );
+        self.port_table_state.select(Some(selected));
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        f.render_stateful_widget(table, area, &mut self.port_table_state);
+    }
+
+    fn render_ets_body(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(6)].as_ref())
+            .split(area);
+        let upper_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(chunks[0]);
+        self.render_ets_tables(f, upper_chunks[0]);
+        self.render_help(f, chunks[1]);
+
+        let chart_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(upper_chunks[1]);
+        let i = self.ets_table_state.selected().unwrap_or(0);
+        if let Some(table) = self.ets_tables.get(i) {
+            self.render_chart(f, chart_chunks[0], &format!("ets.size.{}", table.info.id));
+            self.render_chart(
+                f,
+                chart_chunks[1],
+                &format!("ets.memory_bytes.{}", table.info.id),
+            );
+        }
+    }
+
+    fn render_ets_tables(&mut self, f: &mut Frame, area: Rect) {
+        if self.replay_mode {
+            let paragraph = Paragraph::new(vec![
+                Line::from("The ETS table list is not available in replay mode."),
+                Line::from("See \"ets.size\" and \"ets.memory_bytes\" in the Metrics tab for the largest tables."),
+            ])
+            .block(make_block("ETS Tables (REPLAY)"))
+            .alignment(Alignment::Left);
+            f.render_widget(paragraph, area);
+            return;
+        }
+
+        let block = if self.pause {
+            make_block("ETS Tables (PAUSED)")
+        } else {
+            make_block("ETS Tables")
+        };
+
+        let header_cells = EtsSortKey::COLUMNS.iter().map(|(title, key)| {
+            let title = if *key == Some(self.ets_sort_key) {
+                format!("{title} ▼")
+            } else {
+                title.to_string()
+            };
+            Cell::from(title).style(Style::default().add_modifier(Modifier::BOLD))
+        });
+        let header = Row::new(header_cells).bottom_margin(1);
+
+        let rows = self
+            .ets_tables
+            .iter()
+            .map(|t| {
+                Row::new(vec![
+                    Cell::from(t.info.id.clone()),
+                    Cell::from(t.info.owner.clone()),
+                    Cell::from(t.info.table_type.clone()),
+                    Cell::from(t.info.protection.clone()),
+                    Cell::from(format!("{:>12}", format_u64(t.info.size, ""))),
+                    Cell::from(format!("{:>14}", format_u64(t.memory_bytes, ""))),
+                    Cell::from(format!("{:>12}", format_growth(t.size_growth))),
+                    Cell::from(format!("{:>14}", format_growth(t.memory_growth))),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let widths = [
+            Constraint::Percentage(25),
+            Constraint::Length(16),
+            Constraint::Length(13),
+            Constraint::Length(10),
+            Constraint::Length(14),
+            Constraint::Length(16),
+            Constraint::Length(14),
+            Constraint::Length(16),
+        ];
+        let selected = std::cmp::min(
+            self.ets_table_state.selected().unwrap_or(0),
+            rows.len().saturating_sub(1),
+        );
+        self.ets_table_state.select(Some(selected));
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        f.render_stateful_widget(table, area, &mut self.ets_table_state);
+    }
+
+    fn render_process_detail(&mut self, f: &mut Frame, area: Rect) {
+        let Some(pid) = &self.selected_pid else {
+            return;
+        };
+        let block = make_block(&format!("Detail of {}", pid));
+
+        let Some(detail) = &self.process_detail else {
+            let paragraph = Paragraph::new(vec![Line::from(
+                "No data (the process may have terminated)",
+            )])
+            .block(block)
+            .alignment(Alignment::Left);
+            f.render_widget(paragraph, area);
+            return;
+        };
+
+        let header_cells = ["Item", "Value"]
+            .into_iter()
+            .map(|h| Cell::from(h).style(Style::default().add_modifier(Modifier::BOLD)));
+        let header = Row::new(header_cells).bottom_margin(1);
+
+        let mut prev_key = "";
+        let mut rows = Vec::new();
+        for (key, value) in detail.rows() {
+            let key_cell = if key == prev_key {
+                Cell::from("")
+            } else {
+                Cell::from(key.to_owned())
+            };
+            prev_key = key;
+            rows.push(Row::new(vec![key_cell, Cell::from(value)]));
+        }
+
+        let widths = [Constraint::Length(20), Constraint::Min(0)];
+        let highlight_style = if self.focus == Focus::ProcessDetail {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        let selected = std::cmp::min(
+            self.process_detail_table_state.selected().unwrap_or(0),
+            rows.len().saturating_sub(1),
+        );
+        self.process_detail_table_state.select(Some(selected));
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .highlight_style(highlight_style)
+            .highlight_symbol("> ");
+        f.render_stateful_widget(table, area, &mut self.process_detail_table_state);
+    }
+
+    fn render_processes(&mut self, f: &mut Frame, area: Rect) {
+        if self.replay_mode {
+            let paragraph = Paragraph::new(vec![Line::from(
+                "The process list is not available in replay mode.",
+            )])
+            .block(make_block("Processes (REPLAY)"))
+            .alignment(Alignment::Left);
+            f.render_widget(paragraph, area);
+            return;
+        }
+
+        let block = if self.pause {
+            make_block("Processes (PAUSED)")
+        } else {
+            make_block(&format!("Processes (top {PROCESS_TOP_N})"))
+        };
+
+        let header_cells = ProcessSortKey::COLUMNS.iter().map(|(title, key)| {
+            let title = if *key == Some(self.process_sort_key) {
+                format!("{title} ▼")
+            } else {
+                title.to_string()
+            };
+            Cell::from(title).style(Style::default().add_modifier(Modifier::BOLD))
+        });
+        let header = Row::new(header_cells).bottom_margin(1);
+
+        let rows = self
+            .processes
+            .iter()
+            .take(PROCESS_TOP_N)
+            .map(|p| {
+                Row::new(vec![
+                    Cell::from(p.info.pid.to_string()),
+                    Cell::from(p.info.registered_name.clone().unwrap_or_default()),
+                    Cell::from(p.info.initial_call.clone()),
+                    Cell::from(p.info.current_function.clone()),
+                    Cell::from(format!(
+                        "{:>14}",
+                        p.reductions
+                            .map(|v| format_u64(v.round() as u64, "/s"))
+                            .unwrap_or_default()
+                    )),
+                    Cell::from(format!("{:>14}", format_u64(p.info.memory, ""))),
+                    Cell::from(format!("{:>10}", format_u64(p.info.message_queue_len, ""))),
+                    Cell::from(format!("{:>14}", format_u64(p.heap_bytes, ""))),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Percentage(15),
+            Constraint::Percentage(20),
+            Constraint::Percentage(20),
+            Constraint::Length(16),
+            Constraint::Length(16),
+            Constraint::Length(12),
+            Constraint::Length(16),
+        ];
+        let selected = std::cmp::min(
+            self.process_table_state.selected().unwrap_or(0),
+            rows.len().saturating_sub(1),
+        );
+        self.process_table_state.select(Some(selected));
+
+        let highlight_style = if self.focus == Focus::ProcessDetail {
+            Style::default()
+        } else {
+            Style::default().add_modifier(Modifier::REVERSED)
+        };
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .highlight_style(highlight_style)
+            .highlight_symbol("> ");
+        f.render_stateful_widget(table, area, &mut self.process_table_state);
+    }
+
     fn render_metrics(&mut self, f: &mut Frame, area: Rect) {
         let block = if self.replay_mode {
-            self.make_block("Metrics (REPLAY)")
+            make_block("Metrics (REPLAY)")
         } else if self.pause {
-            self.make_block("Metrics (PAUSED)")
+            make_block("Metrics (PAUSED)")
         } else {
-            self.make_block("Metrics")
+            make_block("Metrics")
         };
 
-        let header_cells = ["Name", "Value", "Avg (1m)"]
+        let avg_header = format!("Avg ({})", self.window.label());
+        let header_cells = ["Name", "Value", avg_header.as_str()]
             .into_iter()
             .map(|h| Cell::from(h).style(Style::default().add_modifier(Modifier::BOLD)));
         let header = Row::new(header_cells).bottom_margin(1);
 
         let items = self.latest_metrics().root_items().collect::<Vec<_>>();
-        let is_avg_available = self.elapsed.as_secs() >= (ONE_MINUTE - 1);
+        let is_avg_available = self.is_avg_available();
         let mut value_width = 0;
         let mut avg_width = 0;
         let mut row_items = Vec::with_capacity(items.len());
         for (name, item) in &items {
-            let value = item.to_string();
+            let limit_percent = self.header.limit_percent(name, item);
+            let value = if let Some(percent) = limit_percent {
+                format!("{item} ({percent:.1}%)")
+            } else {
+                item.to_string()
+            };
             let avg = if is_avg_available {
                 self.averages
                     .get(*name)
@@ -367,15 +1145,34 @@ impl UiState {
             };
             value_width = std::cmp::max(value_width, value.len());
             avg_width = std::cmp::max(avg_width, avg.len());
-            row_items.push((name.to_string(), value, avg));
+            let style = if self.is_alerting(name, true) {
+                alert_style()
+            } else if let Some(percent) = limit_percent {
+                limit_style(percent)
+            } else {
+                Style::default()
+            };
+            row_items.push((name.to_string(), value, avg, style));
+        }
+        for name in &self.latest_metrics().missing {
+            let value = "n/a".to_string();
+            value_width = std::cmp::max(value_width, value.len());
+            row_items.push((name.clone(), value, "".to_string(), Style::default()));
+        }
+        for pattern in &self.header.unavailable_metrics {
+            let value = "n/a".to_string();
+            value_width = std::cmp::max(value_width, value.len());
+            let style = Style::default().fg(Color::DarkGray);
+            row_items.push((pattern.clone(), value, "".to_string(), style));
         }
 
-        let rows = row_items.into_iter().map(|(name, value, avg)| {
+        let rows = row_items.into_iter().map(|(name, value, avg, style)| {
             Row::new(vec![
                 Cell::from(name),
                 Cell::from(format!("{:>value_width$}", value)),
                 Cell::from(format!("{:>avg_width$}", avg)),
             ])
+            .style(style)
         });
 
         let widths = [
@@ -409,79 +1206,136 @@ impl UiState {
             .split(area);
 
         self.render_detail(f, chunks[0]);
-        self.render_chart(f, chunks[1]);
+        self.render_chart(f, chunks[1], self.selected_metric_name());
     }
 
     fn render_help(&mut self, f: &mut Frame, area: Rect) {
         let paragraph = if self.replay_mode {
             Paragraph::new(vec![
-                Line::from("Quit:           'q' key"),
+                Line::from("Quit / Window:  'q' / 'w' keys"),
                 Line::from("Prev / Next:    'h' / 'l' keys"),
                 Line::from("Move:           UP / DOWN / LEFT / RIGHT keys"),
+                Line::from("Switch tab:     TAB key"),
+            ])
+        } else if self.tab == Tab::Ets || self.tab == Tab::Ports {
+            Paragraph::new(vec![
+                Line::from("Quit / Window:  'q' / 'w' keys"),
+                Line::from("Pause / Resume: 'p' key"),
+                Line::from("Move / Sort:    UP / DOWN / 's' keys"),
+                Line::from("Switch tab:     TAB key"),
+            ])
+        } else if self.tab == Tab::Processes {
+            Paragraph::new(vec![
+                Line::from("Quit / Pause / Window: 'q' / 'p' / 'w' keys"),
+                Line::from("Move / Sort:    UP / DOWN / LEFT / RIGHT / 's' keys"),
+                Line::from("Detail / Back:  ENTER / ESC keys"),
+                Line::from("Switch tab:     TAB key"),
             ])
         } else {
             Paragraph::new(vec![
-                Line::from("Quit:           'q' key"),
+                Line::from("Quit / Window:  'q' / 'w' keys"),
                 Line::from("Pause / Resume: 'p' key"),
                 Line::from("Move:           UP / DOWN / LEFT / RIGHT keys"),
+                Line::from("Switch tab:     TAB key"),
             ])
         }
-        .block(self.make_block("Help"))
+        .block(make_block("Help"))
         .alignment(Alignment::Left);
         f.render_widget(paragraph, area);
     }
 
-    fn chart_data(&self) -> (&str, Vec<(f64, f64)>) {
-        let root_metric_name = self
-            .latest_metrics()
+    /// Returns an empty string if there are no metrics (e.g., all of them failed to be collected).
+    fn selected_root_metric_name(&self) -> &str {
+        self.latest_metrics()
             .root_items()
             .nth(self.metrics_table_state.selected().unwrap_or(0))
-            .expect("unreachable")
-            .0;
+            .map_or("", |(name, _)| name)
+    }
 
-        let metric_name = match self.focus {
-            Focus::Main => root_metric_name,
+    fn selected_metric_name(&self) -> &str {
+        let root_metric_name = self.selected_root_metric_name();
+
+        match self.focus {
+            Focus::Main | Focus::ProcessDetail => root_metric_name,
             Focus::Sub => self
                 .latest_metrics()
                 .child_items(root_metric_name)
                 .nth(self.detail_table_state.selected().unwrap_or(0))
                 .map(|(k, _)| k)
                 .unwrap_or(root_metric_name),
-        };
+        }
+    }
 
+    /// Returns data points split into segments at disconnection markers.
+    ///
+    /// Each segment is downsampled so that it has at most about `max_points` points.
+    fn chart_data(&self, metric_name: &str, max_points: usize) -> Vec<Vec<(f64, f64)>> {
         let start = self.history[0].timestamp;
-        let mut data = Vec::with_capacity(self.history.len());
+        let mut segments = vec![Vec::with_capacity(self.history.len())];
         for metrics in &self.history {
+            if metrics.disconnected {
+                segments.push(Vec::new());
+                continue;
+            }
             let x = (metrics.timestamp - start).as_secs_f64();
-            if let Some(y) = metrics.items.get(metric_name).and_then(|x| x.as_f64()) {
-                data.push((x, y));
+            let y = metrics.items.get(metric_name).and_then(|x| {
+                if self.header.system_limits.contains_key(metric_name) {
+                    self.header.limit_percent(metric_name, x)
+                } else {
+                    x.as_f64()
+                }
+            });
+            if let Some(y) = y {
+                segments.last_mut().expect("unreachable").push((x, y));
+            } else if !metrics.errors.is_empty() {
+                // The collector of the metric failed in this sample.
+                segments.push(Vec::new());
             }
         }
-        (metric_name, data)
+        segments.retain(|segment| !segment.is_empty());
+
+        let window = self.window.duration().as_secs_f64();
+        segments
+            .into_iter()
+            .map(|segment| downsample(segment, max_points, window))
+            .collect()
     }
 
-    fn render_chart(&mut self, f: &mut Frame, area: Rect) {
-        let (metric_name, data) = self.chart_data();
-        let block = self.make_block(&format!("Chart of {:?}", metric_name));
+    fn render_chart(&self, f: &mut Frame, area: Rect, metric_name: &str) {
+        let max_points = area.width as usize * CHART_POINTS_PER_CELL;
+        let segments = self.chart_data(metric_name, max_points);
+        let (block, y_suffix) = if self.header.system_limits.contains_key(metric_name) {
+            (
+                make_block(&format!("Chart of {:?} (% of limit)", metric_name)),
+                "%",
+            )
+        } else {
+            (make_block(&format!("Chart of {:?}", metric_name)), "")
+        };
 
-        if data.is_empty() {
+        if segments.is_empty() {
             f.render_widget(block, area);
             return;
         }
 
-        let datasets = vec![Dataset::default()
-            .marker(Marker::Braille)
-            .graph_type(GraphType::Line)
-            .data(&data)];
-
-        let lower_bound = data
+        let datasets = segments
             .iter()
+            .map(|segment| {
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .data(segment)
+            })
+            .collect::<Vec<_>>();
+
+        let data = segments.iter().flatten();
+        let lower_bound = data
+            .clone()
             .map(|(_, y)| *y)
             .min_by(|a, b| a.total_cmp(b))
             .expect("unreachable")
             .floor();
         let mut upper_bound = data
-            .iter()
             .map(|(_, y)| *y)
             .max_by(|a, b| a.total_cmp(b))
             .expect("unreachable")
@@ -493,13 +1347,13 @@ impl UiState {
 
         let y_labels = if is_constant {
             vec![
-                Span::from(format_u64(lower_bound as u64, "")),
+                Span::from(format_u64(lower_bound as u64, y_suffix)),
                 Span::from(""),
             ]
         } else {
             vec![
-                Span::from(format_u64(lower_bound as u64, "")),
-                Span::from(format_u64(upper_bound as u64, "")),
+                Span::from(format_u64(lower_bound as u64, y_suffix)),
+                Span::from(format_u64(upper_bound as u64, y_suffix)),
             ]
         };
 
@@ -507,8 +1361,8 @@ impl UiState {
             .block(block)
             .x_axis(
                 Axis::default()
-                    .labels(vec![Span::from("0s"), Span::from("60s")])
-                    .bounds([0.0, 60.0]),
+                    .labels(vec![Span::from("0s"), Span::from(self.window.label())])
+                    .bounds([0.0, self.window.duration().as_secs_f64()]),
             )
             .y_axis(
                 Axis::default()
@@ -519,26 +1373,22 @@ impl UiState {
     }
 
     fn collect_detailed_items(&self) -> (&str, Vec<(&str, &MetricValue)>) {
-        let root_name = self
-            .latest_metrics()
-            .root_items()
-            .nth(self.metrics_table_state.selected().unwrap_or(0))
-            .expect("unreachable")
-            .0;
+        let root_name = self.selected_root_metric_name();
         let children = self.latest_metrics().child_items(root_name).collect();
         (root_name, children)
     }
 
     fn render_detail(&mut self, f: &mut Frame, area: Rect) {
         let (root_metric_name, items) = self.collect_detailed_items();
-        let block = self.make_block(&format!("Detail of {:?}", root_metric_name));
+        let block = make_block(&format!("Detail of {:?}", root_metric_name));
 
-        let header_cells = ["Name", "Value", "Avg (1m)"]
+        let avg_header = format!("Avg ({})", self.window.label());
+        let header_cells = ["Name", "Value", avg_header.as_str()]
             .into_iter()
             .map(|h| Cell::from(h).style(Style::default().add_modifier(Modifier::BOLD)));
         let header = Row::new(header_cells).bottom_margin(1);
 
-        let is_avg_available = self.start.elapsed().as_secs() >= ONE_MINUTE;
+        let is_avg_available = self.is_avg_available();
         let mut value_width = 0;
         let mut avg_width = 0;
         let mut row_items = Vec::with_capacity(items.len());
@@ -554,15 +1404,21 @@ impl UiState {
             };
             value_width = std::cmp::max(value_width, value.len());
             avg_width = std::cmp::max(avg_width, avg.len());
-            row_items.push((name.to_string(), value, avg));
+            let style = if self.is_alerting(name, false) {
+                alert_style()
+            } else {
+                Style::default()
+            };
+            row_items.push((name.to_string(), value, avg, style));
         }
 
-        let rows = row_items.into_iter().map(|(name, value, avg)| {
+        let rows = row_items.into_iter().map(|(name, value, avg, style)| {
             Row::new(vec![
                 Cell::from(name),
                 Cell::from(format!("{:>value_width$}", value)),
                 Cell::from(format!("{:>avg_width$}", avg)),
             ])
+            .style(style)
         });
 
         let widths = [
@@ -592,15 +1448,223 @@ impl UiState {
         f.render_stateful_widget(table, area, &mut self.detail_table_state);
     }
 
-    fn make_block(&self, name: &str) -> Block<'static> {
-        Block::default().borders(Borders::ALL).title(Span::styled(
-            name.to_string(),
-            Style::default().add_modifier(Modifier::BOLD),
-        ))
+    fn has_metrics(&self) -> bool {
+        self.history.iter().any(|m| !m.disconnected)
     }
 
     fn latest_metrics(&self) -> &Metrics {
-        self.history.back().expect("unreachable")
+        self.history
+            .iter()
+            .rev()
+            .find(|m| !m.disconnected)
+            .expect("unreachable")
+    }
+}
+
+fn disconnected_style() -> Style {
+    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+}
+
+fn alert_style() -> Style {
+    Style::default().fg(Color::Red)
+}
+
+/// Colors a metric by how close its value is to the limit.
+fn limit_style(percent: f64) -> Style {
+    if percent >= 90.0 {
+        Style::default().fg(Color::Red)
+    } else if percent >= 70.0 {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    }
+}
+
+fn make_block(name: &str) -> Block<'static> {
+    Block::default().borders(Borders::ALL).title(Span::styled(
+        name.to_string(),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))
+}
+
+#[derive(Debug)]
+struct ClusterState {
+    header: Header,
+    table_state: TableState,
+    selected_node: Option<usize>,
+}
+
+impl ClusterState {
+    const COLUMNS: &'static [(&'static str, &'static str)] = &[
+        ("Processes", "system_info.process_count"),
+        ("Run Queue", "statistics.run_queue"),
+        ("Scheduler", "utilization.scheduler"),
+        ("Memory", "memory.total_bytes"),
+        ("Reductions", "statistics.exact_reductions"),
+        ("IO Bytes", "statistics.io.total_bytes"),
+    ];
+
+    fn new(header: Header) -> Self {
+        Self {
+            header,
+            table_state: TableState::default(),
+            selected_node: None,
+        }
+    }
+
+    /// Returns `false` if the key is not handled.
+    fn handle_key_event(&mut self, key: KeyEvent, poller: &MetricsPoller, uis: &[UiState]) -> bool {
+        match key.code {
+            KeyCode::Up => {
+                let i = self.table_state.selected().unwrap_or(0).saturating_sub(1);
+                self.table_state.select(Some(i));
+            }
+            KeyCode::Down => {
+                let i = self.table_state.selected().unwrap_or(0) + 1;
+                self.table_state.select(Some(i));
+            }
+            KeyCode::Enter => {
+                let i = self.table_state.selected().unwrap_or(0);
+                if let Some(ui) = uis.get(i) {
+                    self.selected_node = Some(i);
+                    poller.subscribe(i, ui.subscription());
+                }
+            }
+            _ => {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn render(&mut self, f: &mut Frame, uis: &[UiState]) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Min(0),
+                    Constraint::Length(6),
+                ]
+                .as_ref(),
+            )
+            .split(f.size());
+
+        self.render_header(f, chunks[0], uis);
+        self.render_nodes(f, chunks[1], uis);
+        self.render_help(f, chunks[2], uis);
+    }
+
+    fn render_header(&mut self, f: &mut Frame, area: Rect, uis: &[UiState]) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(60),
+                    Constraint::Percentage(20),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        let paragraph = Paragraph::new(vec![Line::from(format!(
+            "{} nodes",
+            self.header.cluster_nodes.len()
+        ))])
+        .block(make_block("Cluster"))
+        .alignment(Alignment::Left);
+        f.render_widget(paragraph, chunks[0]);
+
+        let paragraph = Paragraph::new(vec![Line::from(self.header.system_version.get())])
+            .block(make_block("System Version (Seed Node)"))
+            .alignment(Alignment::Left);
+        f.render_widget(paragraph, chunks[1]);
+
+        let elapsed = uis.iter().map(|ui| ui.elapsed).max().unwrap_or_default();
+        let now = self.header.start_time + elapsed;
+        let paragraph = Paragraph::new(vec![Line::from(
+            now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        )])
+        .block(make_block("Time"))
+        .alignment(Alignment::Left);
+        f.render_widget(paragraph, chunks[2]);
+    }
+
+    fn render_nodes(&mut self, f: &mut Frame, area: Rect, uis: &[UiState]) {
+        let block = if uis[0].replay_mode {
+            make_block("Nodes (REPLAY)")
+        } else if uis[0].pause {
+            make_block("Nodes (PAUSED)")
+        } else {
+            make_block("Nodes")
+        };
+
+        let header_cells = std::iter::once("Node")
+            .chain(Self::COLUMNS.iter().map(|(title, _)| *title))
+            .map(|h| Cell::from(h).style(Style::default().add_modifier(Modifier::BOLD)));
+        let header = Row::new(header_cells).bottom_margin(1);
+
+        let rows = uis
+            .iter()
+            .map(|ui| {
+                let latest = ui.history.iter().rev().find(|m| !m.disconnected);
+                let node = if ui.disconnected {
+                    Line::from(vec![
+                        Span::from(format!("{} ", ui.header.node_name)),
+                        Span::styled("DISCONNECTED", disconnected_style()),
+                    ])
+                } else {
+                    Line::from(ui.header.node_name.clone())
+                };
+                let mut cells = vec![Cell::from(node)];
+                for (_, metric_name) in Self::COLUMNS {
+                    let value = latest
+                        .and_then(|m| m.items.get(*metric_name))
+                        .map(|v| v.to_string())
+                        .unwrap_or_else(|| "-".to_owned());
+                    cells.push(Cell::from(format!("{value:>16}")));
+                }
+                Row::new(cells)
+            })
+            .collect::<Vec<_>>();
+
+        let widths = std::iter::once(Constraint::Percentage(25))
+            .chain(Self::COLUMNS.iter().map(|_| Constraint::Length(18)))
+            .collect::<Vec<_>>();
+        let selected = std::cmp::min(
+            self.table_state.selected().unwrap_or(0),
+            rows.len().saturating_sub(1),
+        );
+        self.table_state.select(Some(selected));
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        f.render_stateful_widget(table, area, &mut self.table_state);
+    }
+
+    fn render_help(&mut self, f: &mut Frame, area: Rect, uis: &[UiState]) {
+        let paragraph = if uis[0].replay_mode {
+            Paragraph::new(vec![
+                Line::from("Quit:           'q' key"),
+                Line::from("Prev / Next:    'h' / 'l' keys"),
+                Line::from("Move:           UP / DOWN keys"),
+                Line::from("Node dashboard: ENTER key"),
+            ])
+        } else {
+            Paragraph::new(vec![
+                Line::from("Quit:           'q' key"),
+                Line::from("Pause / Resume: 'p' key"),
+                Line::from("Move:           UP / DOWN keys"),
+                Line::from("Node dashboard: ENTER key"),
+            ])
+        }
+        .block(make_block("Help"))
+        .alignment(Alignment::Left);
+        f.render_widget(paragraph, area);
     }
 }
 
@@ -608,60 +1672,198 @@ impl UiState {
 enum Focus {
     Main,
     Sub,
+    ProcessDetail,
 }
 
-#[derive(Debug, Clone)]
-struct AvgValue {
-    sum: MetricValue,
-    cnt: usize,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Tab {
+    Metrics,
+    Processes,
+    Ports,
+    Ets,
 }
 
-impl AvgValue {
-    fn new(value: MetricValue) -> Self {
-        Self { sum: value, cnt: 1 }
+impl Tab {
+    const ALL: &'static [Self] = &[Self::Metrics, Self::Processes, Self::Ports, Self::Ets];
+
+    fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|tab| *tab == self)
+            .expect("unreachable");
+        Self::ALL[(i + 1) % Self::ALL.len()]
     }
 
-    fn add(&mut self, v: MetricValue) {
-        self.sum += v;
-        self.cnt += 1;
+    fn title(self) -> &'static str {
+        match self {
+            Self::Metrics => "Metrics",
+            Self::Processes => "Processes",
+            Self::Ports => "Ports",
+            Self::Ets => "ETS",
+        }
     }
+}
 
-    fn sub(&mut self, v: MetricValue) {
-        self.sum -= v;
-        self.cnt -= 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ProcessSortKey {
+    Reductions,
+    Memory,
+    MessageQueueLen,
+    HeapSize,
+}
+
+impl ProcessSortKey {
+    const COLUMNS: &'static [(&'static str, Option<Self>)] = &[
+        ("Pid", None),
+        ("Name", None),
+        ("Initial Call", None),
+        ("Current Function", None),
+        ("Reductions", Some(Self::Reductions)),
+        ("Memory", Some(Self::Memory)),
+        ("MsgQ", Some(Self::MessageQueueLen)),
+        ("Heap", Some(Self::HeapSize)),
+    ];
+
+    fn next(self) -> Self {
+        match self {
+            Self::Reductions => Self::Memory,
+            Self::Memory => Self::MessageQueueLen,
+            Self::MessageQueueLen => Self::HeapSize,
+            Self::HeapSize => Self::Reductions,
+        }
     }
 
-    fn get(&self) -> MetricValue {
-        match self.sum {
-            MetricValue::Gauge { value, .. } => {
-                let value = (value as f64 / self.cnt as f64).round() as u64;
-                MetricValue::Gauge {
-                    value,
-                    parent: None,
-                }
-            }
-            MetricValue::Counter {
-                value: Some(value), ..
-            } => {
-                let value = value / self.cnt as f64;
-                MetricValue::Counter {
-                    raw_value: 0,
-                    value: Some(value),
-                    parent: None,
-                }
+    fn sort_value(self, process: &ProcessMetrics) -> f64 {
+        match self {
+            Self::Reductions => process.reductions.unwrap_or_default(),
+            Self::Memory => process.info.memory as f64,
+            Self::MessageQueueLen => process.info.message_queue_len as f64,
+            Self::HeapSize => process.heap_bytes as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PortSortKey {
+    TotalBytes,
+    InputBytes,
+    OutputBytes,
+    QueueSize,
+}
+
+impl PortSortKey {
+    const COLUMNS: &'static [(&'static str, Option<Self>)] = &[
+        ("Port", None),
+        ("Name", None),
+        ("Connected", None),
+        ("OS PID", None),
+        ("Input", Some(Self::InputBytes)),
+        ("Output", Some(Self::OutputBytes)),
+        ("Queue", Some(Self::QueueSize)),
+        ("Recv Cnt", None),
+        ("Send Cnt", None),
+        ("Send Pend", None),
+    ];
+
+    fn next(self) -> Self {
+        match self {
+            Self::TotalBytes => Self::InputBytes,
+            Self::InputBytes => Self::OutputBytes,
+            Self::OutputBytes => Self::QueueSize,
+            Self::QueueSize => Self::TotalBytes,
+        }
+    }
+
+    fn sort_value(self, port: &PortMetrics) -> f64 {
+        let input = port.input_bytes.unwrap_or_default();
+        let output = port.output_bytes.unwrap_or_default();
+        match self {
+            Self::TotalBytes => input + output,
+            Self::InputBytes => input,
+            Self::OutputBytes => output,
+            Self::QueueSize => port.info.queue_size as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum EtsSortKey {
+    Size,
+    Memory,
+    SizeGrowth,
+    MemoryGrowth,
+}
+
+impl EtsSortKey {
+    const COLUMNS: &'static [(&'static str, Option<Self>)] = &[
+        ("Name", None),
+        ("Owner", None),
+        ("Type", None),
+        ("Protection", None),
+        ("Size", Some(Self::Size)),
+        ("Memory", Some(Self::Memory)),
+        ("Size Growth", Some(Self::SizeGrowth)),
+        ("Memory Growth", Some(Self::MemoryGrowth)),
+    ];
+
+    fn next(self) -> Self {
+        match self {
+            Self::Size => Self::Memory,
+            Self::Memory => Self::SizeGrowth,
+            Self::SizeGrowth => Self::MemoryGrowth,
+            Self::MemoryGrowth => Self::Size,
+        }
+    }
+
+    fn sort_value(self, table: &EtsTableMetrics) -> f64 {
+        match self {
+            Self::Size => table.info.size as f64,
+            Self::Memory => table.memory_bytes as f64,
+            Self::SizeGrowth => table.size_growth.unwrap_or_default(),
+            Self::MemoryGrowth => table.memory_growth.unwrap_or_default(),
+        }
+    }
+}
+
+/// Reduces the number of points by keeping only the minimum and maximum points of each bucket.
+///
+/// The x range `0.0..=x_max` is split into `max_points / 2` buckets.
+fn downsample(points: Vec<(f64, f64)>, max_points: usize, x_max: f64) -> Vec<(f64, f64)> {
+    let buckets = max_points / 2;
+    if points.len() <= max_points || buckets == 0 {
+        return points;
+    }
+
+    let bucket_width = x_max / buckets as f64;
+    let bucket_of = |(x, _): (f64, f64)| (x / bucket_width) as usize;
+    let mut result = Vec::with_capacity(max_points);
+    let mut i = 0;
+    while i < points.len() {
+        let bucket = bucket_of(points[i]);
+        let (mut min, mut max) = (i, i);
+        let mut j = i + 1;
+        while j < points.len() && bucket_of(points[j]) == bucket {
+            if points[j].1 < points[min].1 {
+                min = j;
             }
-            MetricValue::Counter { .. } => MetricValue::Counter {
-                raw_value: 0,
-                value: None,
-                parent: None,
-            },
-            MetricValue::Utilization { value, .. } => {
-                let value = value / self.cnt as f64;
-                MetricValue::Utilization {
-                    value,
-                    parent: None,
-                }
+            if points[j].1 > points[max].1 {
+                max = j;
             }
+            j += 1;
+        }
+        result.push(points[std::cmp::min(min, max)]);
+        if min != max {
+            result.push(points[std::cmp::max(min, max)]);
         }
+        i = j;
+    }
+    result
+}
+
+fn format_growth(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v < 0.0 => format!("-{}", format_u64((-v).round() as u64, "/s")),
+        Some(v) => format!("+{}", format_u64(v.round() as u64, "/s")),
+        None => String::new(),
     }
 }